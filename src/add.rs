@@ -0,0 +1,321 @@
+use std::fs::{self, Metadata};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::multipart::{Multipart, Upload};
+use crate::query::Query;
+
+/// Options for `add_with` and `add_path`. Anything left unset is up to the daemon.
+///
+/// ```no_run
+/// # use ipfs_api::{AddOptions, IPFS};
+/// # let ipfs = IPFS::new();
+/// let added = ipfs.add_path("./site", AddOptions::new().recursive(true).ignore("*.tmp")).unwrap();
+/// println!("{}", added.root);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AddOptions {
+    wrap_with_directory: Option<bool>,
+    recursive: bool,
+    hidden: bool,
+    ignore: Vec<String>,
+    ignore_rules_path: Option<PathBuf>,
+    pin: Option<bool>,
+    only_hash: Option<bool>,
+    chunker: Option<String>,
+    raw_leaves: Option<bool>,
+    cid_version: Option<u32>,
+    hash: Option<String>,
+    trickle: Option<bool>,
+    nocopy: Option<bool>,
+    inline: Option<bool>,
+    inline_limit: Option<u32>,
+}
+
+impl AddOptions {
+
+    pub fn new() -> AddOptions {
+        Default::default()
+    }
+
+    /// Wraps what is added in a directory, so the root keeps the original file name.
+    pub fn wrap_with_directory(&mut self, inp: bool) -> &mut AddOptions {
+        self.wrap_with_directory = Some(inp);
+        self
+    }
+
+    /// Adds directories with everything inside them. Adding a directory fails without it.
+    pub fn recursive(&mut self, inp: bool) -> &mut AddOptions {
+        self.recursive = inp;
+        self
+    }
+
+    /// Includes files and directories whose name starts with a dot.
+    pub fn hidden(&mut self, inp: bool) -> &mut AddOptions {
+        self.hidden = inp;
+        self
+    }
+
+    /// Skips entries matching the `.gitignore`-style `pattern`.
+    ///
+    /// A pattern without a slash matches entry names anywhere in the tree, one with a slash
+    /// matches paths relative to the added directory. A trailing slash only matches directories.
+    /// `*` and `?` never match a slash, `**` does. Negated (`!`) patterns are not supported.
+    pub fn ignore(&mut self, pattern: &str) -> &mut AddOptions {
+        self.ignore.push(pattern.to_string());
+        self
+    }
+
+    /// Reads more `ignore` patterns, one per line, from a `.gitignore`-style file.
+    pub fn ignore_rules_path<P: AsRef<Path>>(&mut self, path: P) -> &mut AddOptions {
+        self.ignore_rules_path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Pins what is added. The daemon pins by default.
+    pub fn pin(&mut self, inp: bool) -> &mut AddOptions {
+        self.pin = Some(inp);
+        self
+    }
+
+    /// Only computes the CIDs, without writing anything to the repo.
+    pub fn only_hash(&mut self, inp: bool) -> &mut AddOptions {
+        self.only_hash = Some(inp);
+        self
+    }
+
+    /// The chunking algorithm, e.g. `size-262144` or `rabin-262144-524288-1048576`.
+    pub fn chunker(&mut self, inp: &str) -> &mut AddOptions {
+        self.chunker = Some(inp.to_string());
+        self
+    }
+
+    /// Stores file data in raw leaves instead of wrapping it in UnixFS nodes.
+    pub fn raw_leaves(&mut self, inp: bool) -> &mut AddOptions {
+        self.raw_leaves = Some(inp);
+        self
+    }
+
+    /// The CID version of the result. Version 1 also turns on raw leaves unless set otherwise.
+    pub fn cid_version(&mut self, inp: u32) -> &mut AddOptions {
+        self.cid_version = Some(inp);
+        self
+    }
+
+    /// The multihash function, e.g. `sha2-256` or `blake2b-256`.
+    pub fn hash(&mut self, inp: &str) -> &mut AddOptions {
+        self.hash = Some(inp.to_string());
+        self
+    }
+
+    /// Lays the DAG out as a trickle DAG instead of a balanced one.
+    pub fn trickle(&mut self, inp: bool) -> &mut AddOptions {
+        self.trickle = Some(inp);
+        self
+    }
+
+    /// References the files through the filestore instead of copying them into the repo.
+    /// The daemon needs the filestore experiment enabled and must see the same paths.
+    pub fn nocopy(&mut self, inp: bool) -> &mut AddOptions {
+        self.nocopy = Some(inp);
+        self
+    }
+
+    /// Inlines small blocks into their CIDs.
+    pub fn inline(&mut self, inp: bool) -> &mut AddOptions {
+        self.inline = Some(inp);
+        self
+    }
+
+    /// The largest block, in bytes, that `inline` puts into a CID.
+    pub fn inline_limit(&mut self, inp: u32) -> &mut AddOptions {
+        self.inline_limit = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .opt("wrap-with-directory", self.wrap_with_directory)
+            .opt("pin", self.pin)
+            .opt("only-hash", self.only_hash)
+            .opt("chunker", self.chunker.as_ref())
+            .opt("raw-leaves", self.raw_leaves)
+            .opt("cid-version", self.cid_version)
+            .opt("hash", self.hash.as_ref())
+            .opt("trickle", self.trickle)
+            .opt("nocopy", self.nocopy)
+            .opt("inline", self.inline)
+            .opt("inline-limit", self.inline_limit)
+    }
+
+    /// Builds the multipart body for `path`: one part per file, directory and symlink, named by
+    /// their path relative to the parent of `path`, parents always coming before their children.
+    pub(crate) fn walk(&self, path: &Path) -> io::Result<Multipart> {
+        let rules = self.rules()?;
+        let name = root_name(path)?;
+        let meta = fs::metadata(path)?;
+        let mut form = Multipart::new();
+        if !meta.is_dir() {
+            return Ok(form.add("arg", Upload::path(path)));
+        }
+        if !self.recursive {
+            return Err(io::Error::new(io::ErrorKind::InvalidInput,
+                format!("{} is a directory, add it recursively", path.display())));
+        }
+        form = form.add("arg", Upload::directory(name.clone()));
+        self.walk_dir(form, path, &name, "", &rules)
+    }
+
+    fn walk_dir(&self, mut form: Multipart, dir: &Path, name: &str,
+                relative: &str, rules: &[Rule]) -> io::Result<Multipart> {
+        let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+        entries.sort_by_key(|e| e.file_name());
+        for entry in entries {
+            let file_name = entry.file_name().to_string_lossy().into_owned();
+            if file_name.starts_with('.') && !self.hidden {
+                continue;
+            }
+            let meta: Metadata = fs::symlink_metadata(entry.path())?;
+            let entry_relative = format!("{}/{}", relative, file_name);
+            if rules.iter().any(|r| r.matches(&file_name, &entry_relative[1..], meta.is_dir())) {
+                continue;
+            }
+            let entry_name = format!("{}/{}", name, file_name);
+            if meta.file_type().is_symlink() {
+                form = form.add("arg", Upload::symlink(entry_name, fs::read_link(entry.path())?));
+            } else if meta.is_dir() {
+                form = form.add("arg", Upload::directory(entry_name.clone()));
+                form = self.walk_dir(form, &entry.path(), &entry_name, &entry_relative, rules)?;
+            } else {
+                form = form.add("arg", Upload::path(entry.path()).named(entry_name));
+            }
+        }
+        Ok(form)
+    }
+
+    fn rules(&self) -> io::Result<Vec<Rule>> {
+        let mut patterns = self.ignore.clone();
+        if let Some(ref path) = self.ignore_rules_path {
+            let content = fs::read_to_string(path)?;
+            patterns.extend(content.lines().map(|l| l.to_string()));
+        }
+        Ok(patterns.iter().filter_map(|p| Rule::parse(p)).collect())
+    }
+}
+
+/// The name the daemon gets for the added root, resolving `.` and the like.
+fn root_name(path: &Path) -> io::Result<String> {
+    let name = match path.file_name() {
+        Some(name) => name.to_os_string(),
+        None => fs::canonicalize(path)?.file_name().map(|n| n.to_os_string()).unwrap_or_default(),
+    };
+    Ok(name.to_string_lossy().into_owned())
+}
+
+struct Rule {
+    pattern: String,
+    anchored: bool,
+    dir_only: bool,
+}
+
+impl Rule {
+    fn parse(line: &str) -> Option<Rule> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('!') {
+            return None;
+        }
+        let dir_only = line.ends_with('/');
+        let line = line.trim_end_matches('/');
+        Some(Rule {
+            anchored: line.contains('/'),
+            pattern: line.trim_start_matches('/').to_string(),
+            dir_only,
+        })
+    }
+
+    fn matches(&self, name: &str, relative: &str, is_dir: bool) -> bool {
+        if self.dir_only && !is_dir {
+            return false;
+        }
+        let target = if self.anchored { relative } else { name };
+        glob(self.pattern.as_bytes(), target.as_bytes())
+    }
+}
+
+fn glob(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern.first() {
+        None => text.is_empty(),
+        Some(b'*') if pattern.get(1) == Some(&b'*') => {
+            let rest = pattern[2..].strip_prefix(b"/").unwrap_or(&pattern[2..]);
+            (0..=text.len()).any(|i| glob(rest, &text[i..]))
+        }
+        Some(b'*') => {
+            let end = text.iter().position(|b| *b == b'/').unwrap_or(text.len());
+            (0..=end).any(|i| glob(&pattern[1..], &text[i..]))
+        }
+        Some(b'?') => !text.is_empty() && text[0] != b'/' && glob(&pattern[1..], &text[1..]),
+        Some(c) => text.first() == Some(c) && glob(&pattern[1..], &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn globs() {
+        assert!(glob(b"*.tmp", b"a.tmp"));
+        assert!(!glob(b"*.tmp", b"dir/a.tmp"));
+        assert!(glob(b"**/a.tmp", b"dir/sub/a.tmp"));
+        assert!(glob(b"**/a.tmp", b"a.tmp"));
+        assert!(glob(b"build/**", b"build/x/y"));
+        assert!(glob(b"?.txt", b"a.txt"));
+        assert!(!glob(b"?.txt", b"ab.txt"));
+    }
+
+    #[test]
+    fn rules_follow_gitignore_anchoring() {
+        let anywhere = Rule::parse("target").unwrap();
+        assert!(anywhere.matches("target", "sub/target", true));
+        let anchored = Rule::parse("/docs/*.md").unwrap();
+        assert!(anchored.matches("a.md", "docs/a.md", false));
+        assert!(!anchored.matches("a.md", "sub/docs/a.md", false));
+        let dirs = Rule::parse("cache/").unwrap();
+        assert!(!dirs.matches("cache", "cache", false));
+        assert!(Rule::parse("# comment").is_none());
+    }
+
+    #[test]
+    fn walk_sends_parents_first_and_skips_ignored() {
+        use std::io::Read;
+        let root = std::env::temp_dir().join(format!("ipfs-api-walk-{}", std::process::id()));
+        fs::create_dir_all(root.join("sub")).unwrap();
+        fs::write(root.join("b.txt"), "b").unwrap();
+        fs::write(root.join("a.tmp"), "a").unwrap();
+        fs::write(root.join(".hidden"), "h").unwrap();
+        fs::write(root.join("sub/c.txt"), "c").unwrap();
+        let mut body = String::new();
+        AddOptions::new().recursive(true).ignore("*.tmp").walk(&root).unwrap()
+            .into_reader().unwrap().read_to_string(&mut body).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+        let name = root.file_name().unwrap().to_str().unwrap();
+        let names: Vec<&str> = body.split("filename=\"").skip(1).map(|s| &s[..s.find('"').unwrap()]).collect();
+        let expected: Vec<String> = vec!["".to_string(), "/b.txt".to_string(), "/sub".to_string(), "/sub/c.txt".to_string()]
+            .into_iter().map(|s| format!("{}{}", name, s)).collect();
+        assert_eq!(expected, names);
+    }
+
+    #[test]
+    fn walking_options_stay_local() {
+        assert!(AddOptions::new().recursive(true).hidden(true).ignore("*.tmp").args().is_empty());
+        let args = AddOptions::new().wrap_with_directory(true).raw_leaves(false).inline_limit(32).args();
+        assert_eq!("wrap-with-directory=true&raw-leaves=false&inline-limit=32", args.to_string());
+    }
+
+    #[test]
+    fn directories_need_recursive() {
+        let err = AddOptions::new().walk(Path::new("src")).err().unwrap();
+        assert_eq!(io::ErrorKind::InvalidInput, err.kind());
+    }
+}
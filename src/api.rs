@@ -0,0 +1,699 @@
+use std::io;
+use std::path::Path;
+use std::time::Duration;
+
+use crate::add::AddOptions;
+use crate::endpoint::Endpoint;
+use crate::files::FilesWriteOptions;
+use crate::multibase;
+use crate::multipart::{Multipart, Upload};
+use crate::params::*;
+use crate::query::Query;
+use crate::response::PubsubEncoding;
+
+/// Everything needed to issue one call against the daemon.
+pub(crate) struct Request {
+    pub url: &'static str,
+    pub args: Query,
+    /// Multipart body, if the call uploads something.
+    pub body: Option<Multipart>,
+}
+
+impl Request {
+    fn url(url: &'static str) -> Request {
+        Request { url, args: Query::new(), body: None }
+    }
+
+    fn args(mut self, inp: Query) -> Request {
+        self.args = inp;
+        self
+    }
+
+    fn file(mut self, upload: Upload) -> Request {
+        self.body = Some(Multipart::new().add("arg", upload));
+        self
+    }
+
+    fn form(mut self, form: Multipart) -> Request {
+        self.body = Some(form);
+        self
+    }
+
+    /// The command this calls, as the daemon names it in `commands`, e.g. `files/ls`.
+    pub fn command(&self) -> &'static str {
+        self.url.trim_start_matches("/api/v0/")
+    }
+
+    pub fn link(&self, endpoint: &Endpoint) -> String {
+        let l = format!("{}{}", endpoint.base(), self.url);
+        if !self.args.is_empty() {
+            return format!("{}?{}", l, self.args)
+        }
+        l
+    }
+}
+
+//--------------------------------------- API FUNCTIONS ---------------------------------------
+
+
+pub(crate) fn add(file: Upload) -> Request {
+    Request::url("/api/v0/add").file(file)
+}
+
+pub(crate) fn add_with(file: Upload, options: &AddOptions) -> Request {
+    let l = options.args();
+    Request::url("/api/v0/add").args(l).file(file)
+}
+
+pub(crate) fn add_progress(file: Upload, options: &AddOptions) -> Request {
+    let l = progress_args(options);
+    Request::url("/api/v0/add").args(l).file(file)
+}
+
+pub(crate) fn add_path(path: &Path, options: &AddOptions) -> io::Result<Request> {
+    let l = options.args();
+    Ok(Request::url("/api/v0/add").args(l).form(options.walk(path)?))
+}
+
+pub(crate) fn add_path_progress(path: &Path, options: &AddOptions) -> io::Result<Request> {
+    let l = progress_args(options);
+    Ok(Request::url("/api/v0/add").args(l).form(options.walk(path)?))
+}
+
+fn progress_args(options: &AddOptions) -> Query {
+    options.args().pair("progress", true)
+}
+
+pub(crate) fn bitswap_ledger(peer: &str) -> Request {
+    let l = Query::new().arg(peer);
+    Request::url("/api/v0/bitswap/ledger").args(l)
+}
+
+pub(crate) fn bitswap_stat() -> Request {
+    Request::url("/api/v0/bitswap/stat")
+}
+
+pub(crate) fn bitswap_unwant(key: &str) -> Request {
+    let l = Query::new().arg(key);
+    Request::url("/api/v0/bitswap/unwant").args(l)
+}
+
+pub(crate) fn bitswap_wantlist(peer: &str) -> Request {
+    let l = Query::new().arg(peer);
+    Request::url("/api/v0/bitswap/wantlist").args(l)
+}
+
+pub(crate) fn block_get(hash: &str) -> Request {
+    let l = Query::new().arg(hash);
+    Request::url("/api/v0/block/get").args(l)
+}
+
+pub(crate) fn block_put(file: Upload, req: &BlockPut) -> Request {
+    Request::url("/api/v0/block/put").args(req.args()).file(file)
+}
+
+pub(crate) fn block_rm(req: &BlockRm) -> Request {
+    Request::url("/api/v0/block/rm").args(req.args())
+}
+
+pub(crate) fn block_stat(hash: &str) -> Request {
+    let l = Query::new().arg(hash);
+    Request::url("/api/v0/block/stat").args(l)
+}
+
+pub(crate) fn bootstrap_add(peers: &[&str]) -> Request {
+    let l = peers.iter().fold(Query::new(), |l, peer| l.arg(peer));
+    Request::url("/api/v0/bootstrap/add").args(l)
+}
+
+pub(crate) fn bootstrap_add_default() -> Request {
+    Request::url("/api/v0/bootstrap/add/default")
+}
+
+pub(crate) fn bootstrap_list() -> Request {
+    Request::url("/api/v0/bootstrap/list")
+}
+
+pub(crate) fn bootstrap_rm(peers: &[&str]) -> Request {
+    let l = peers.iter().fold(Query::new(), |l, peer| l.arg(peer));
+    Request::url("/api/v0/bootstrap/rm").args(l)
+}
+
+pub(crate) fn bootstrap_rm_all() -> Request {
+    Request::url("/api/v0/bootstrap/rm/all")
+}
+
+pub(crate) fn cat(hash: &str) -> Request {
+    let l = Query::new().arg(hash);
+    Request::url("/api/v0/cat").args(l)
+}
+
+pub(crate) fn cid_base32(cid: &str) -> Request {
+    let l = Query::new().arg(cid);
+    Request::url("/api/v0/cid/base32").args(l)
+}
+
+pub(crate) fn cid_bases() -> Request {
+    Request::url("/api/v0/cid/bases")
+}
+
+pub(crate) fn cid_codecs() -> Request {
+    Request::url("/api/v0/cid/codecs")
+}
+
+pub(crate) fn cid_format(req: &CidFormat) -> Request {
+    Request::url("/api/v0/cid/format").args(req.args())
+}
+
+pub(crate) fn cid_hashes() -> Request {
+    Request::url("/api/v0/cid/hashes")
+}
+
+pub(crate) fn commands() -> Request {
+    let l = Query::new().pair("flags", true);
+    Request::url("/api/v0/commands").args(l)
+}
+
+pub(crate) fn config_replace(file: Upload) -> Request {
+    Request::url("/api/v0/config/replace").file(file)
+}
+
+pub(crate) fn config_show() -> Request {
+    Request::url("/api/v0/config/show")
+}
+
+pub(crate) fn dag_export(cid: &str) -> Request {
+    let l = Query::new().arg(cid);
+    Request::url("/api/v0/dag/export").args(l)
+}
+
+pub(crate) fn dag_get(inp: &str) -> Request {
+    let l = Query::new().arg(inp);
+    Request::url("/api/v0/dag/get").args(l)
+}
+
+pub(crate) fn dag_import(file: Upload, req: &DagImport) -> Request {
+    Request::url("/api/v0/dag/import").args(req.args()).file(file)
+}
+
+pub(crate) fn dag_put(file: Upload, req: &DagPut) -> Request {
+    Request::url("/api/v0/dag/put").args(req.args()).file(file)
+}
+
+pub(crate) fn dag_resolve(ipath: &str) -> Request {
+    let l = Query::new().arg(ipath);
+    Request::url("/api/v0/dag/resolve").args(l)
+}
+
+pub(crate) fn dag_stat(cid: &str) -> Request {
+    let l = Query::new().arg(cid);
+    Request::url("/api/v0/dag/stat").args(l)
+}
+
+pub(crate) fn dht_findpeer(req: &DhtQuery) -> Request {
+    Request::url("/api/v0/dht/findpeer").args(req.args())
+}
+
+pub(crate) fn dht_findprovs(req: &DhtQuery) -> Request {
+    Request::url("/api/v0/dht/findprovs").args(req.args())
+}
+
+pub(crate) fn dht_get(req: &DhtQuery) -> Request {
+    Request::url("/api/v0/dht/get").args(req.args())
+}
+
+pub(crate) fn dht_provide(req: &DhtProvide) -> Request {
+    Request::url("/api/v0/dht/provide").args(req.args())
+}
+
+pub(crate) fn dht_put(req: &DhtPut) -> Request {
+    Request::url("/api/v0/dht/put").args(req.args())
+}
+
+pub(crate) fn dht_query(req: &DhtQuery) -> Request {
+    Request::url("/api/v0/dht/query").args(req.args())
+}
+
+pub(crate) fn diag_cmds_clear() -> Request {
+    Request::url("/api/v0/diag/cmds/clear")
+}
+
+pub(crate) fn diag_profile(profile_time: Duration) -> Request {
+    let l = Query::new().pair("profile-time", format!("{}ms", profile_time.as_millis()));
+    Request::url("/api/v0/diag/profile").args(l)
+}
+
+pub(crate) fn diag_set_time(ntime: &str) -> Request {
+    let l = Query::new().arg(ntime);
+    Request::url("/api/v0/diag/cmds/set-time").args(l)
+}
+
+pub(crate) fn diag_net(req: &DiagNet) -> Request {
+    Request::url("/api/v0/diag/net").args(req.args())
+}
+
+pub(crate) fn diag_sys() -> Request {
+    Request::url("/api/v0/diag/sys")
+}
+
+pub(crate) fn dns(req: &Dns) -> Request {
+    Request::url("/api/v0/dns").args(req.args())
+}
+
+pub(crate) fn file_ls(ipath: &str) -> Request {
+    let l = Query::new().arg(ipath);
+    Request::url("/api/v0/file/ls").args(l)
+}
+
+pub(crate) fn files_chcid(ipath: &str, cid_version: Option<u32>, hash: Option<&str>) -> Request {
+    let l = Query::new().arg(ipath).opt("cid-version", cid_version).opt("hash", hash);
+    Request::url("/api/v0/files/chcid").args(l)
+}
+
+pub(crate) fn files_cp(from: &str, to: &str) -> Request {
+    let l = Query::new().arg(from).arg(to);
+    Request::url("/api/v0/files/cp").args(l)
+}
+
+pub(crate) fn files_flush(ipath: &str) -> Request {
+    let l = Query::new().arg(ipath);
+    Request::url("/api/v0/files/flush").args(l)
+}
+
+pub(crate) fn files_ls(req: &FilesLs) -> Request {
+    Request::url("/api/v0/files/ls").args(req.args())
+}
+
+pub(crate) fn files_mkdir(req: &FilesMkdir) -> Request {
+    Request::url("/api/v0/files/mkdir").args(req.args())
+}
+
+pub(crate) fn files_mv(source: &str, dest: &str) -> Request {
+    let l = Query::new().arg(source).arg(dest);
+    Request::url("/api/v0/files/mv").args(l)
+}
+
+pub(crate) fn files_read(req: &FilesRead) -> Request {
+    Request::url("/api/v0/files/read").args(req.args())
+}
+
+pub(crate) fn files_rm(req: &FilesRm) -> Request {
+    Request::url("/api/v0/files/rm").args(req.args())
+}
+
+pub(crate) fn files_stat(req: &FilesStat) -> Request {
+    Request::url("/api/v0/files/stat").args(req.args())
+}
+
+pub(crate) fn files_write(ipath: &str, file: Upload, options: &FilesWriteOptions) -> Request {
+    let l = options.args().arg(ipath);
+    Request::url("/api/v0/files/write").args(l).file(file)
+}
+
+pub(crate) fn filestore_dups() -> Request {
+    Request::url("/api/v0/filestore/dups")
+}
+
+pub(crate) fn filestore_ls(cid: &str) -> Request {
+    let l = Query::new().arg(cid);
+    Request::url("/api/v0/filestore/ls").args(l)
+}
+
+pub(crate) fn filestore_verify(cid: &str) -> Request {
+    let l = Query::new().arg(cid);
+    Request::url("/api/v0/filestore/verify").args(l)
+}
+
+pub(crate) fn get(req: &Get) -> Request {
+    Request::url("/api/v0/get").args(req.args())
+}
+
+pub(crate) fn id(req: &Id) -> Request {
+    Request::url("/api/v0/id").args(req.args())
+}
+
+pub(crate) fn key_export(name: &str, format: Option<&str>) -> Request {
+    let l = Query::new().arg(name).opt("format", format);
+    Request::url("/api/v0/key/export").args(l)
+}
+
+pub(crate) fn key_gen(req: &KeyGen) -> Request {
+    Request::url("/api/v0/key/gen").args(req.args())
+}
+
+pub(crate) fn key_import(name: &str, key: Upload, format: Option<&str>) -> Request {
+    let l = Query::new().arg(name).opt("format", format);
+    Request::url("/api/v0/key/import").args(l).file(key)
+}
+
+pub(crate) fn key_list(req: &KeyList) -> Request {
+    Request::url("/api/v0/key/list").args(req.args())
+}
+
+pub(crate) fn key_rename(req: &KeyRename) -> Request {
+    Request::url("/api/v0/key/rename").args(req.args())
+}
+
+pub(crate) fn key_rm(names: &[&str]) -> Request {
+    let l = names.iter().fold(Query::new(), |l, name| l.arg(name));
+    Request::url("/api/v0/key/rm").args(l)
+}
+
+pub(crate) fn log_level(sli: &str, level: &str) -> Request {
+    let l = Query::new().arg(sli).arg(level);
+    Request::url("/api/v0/log/level").args(l)
+}
+
+pub(crate) fn log_ls() -> Request {
+    Request::url("/api/v0/log/ls")
+}
+
+pub(crate) fn log_tail() -> Request {
+    Request::url("/api/v0/log/tail")
+}
+
+pub(crate) fn ls(req: &Ls) -> Request {
+    Request::url("/api/v0/ls").args(req.args())
+}
+
+pub(crate) fn mount(req: &Mount) -> Request {
+    Request::url("/api/v0/mount").args(req.args())
+}
+
+pub(crate) fn multibase_decode(file: Upload) -> Request {
+    Request::url("/api/v0/multibase/decode").file(file)
+}
+
+pub(crate) fn multibase_encode(file: Upload, base: &str) -> Request {
+    let l = Query::new().pair("b", base);
+    Request::url("/api/v0/multibase/encode").args(l).file(file)
+}
+
+pub(crate) fn multibase_list() -> Request {
+    Request::url("/api/v0/multibase/list")
+}
+
+pub(crate) fn multibase_transcode(file: Upload, base: &str) -> Request {
+    let l = Query::new().pair("b", base);
+    Request::url("/api/v0/multibase/transcode").args(l).file(file)
+}
+
+pub(crate) fn name_publish(req: &NamePublish) -> Request {
+    Request::url("/api/v0/name/publish").args(req.args())
+}
+
+pub(crate) fn name_pubsub_cancel(name: &str) -> Request {
+    let l = Query::new().arg(name);
+    Request::url("/api/v0/name/pubsub/cancel").args(l)
+}
+
+pub(crate) fn name_pubsub_state() -> Request {
+    Request::url("/api/v0/name/pubsub/state")
+}
+
+pub(crate) fn name_pubsub_subs() -> Request {
+    Request::url("/api/v0/name/pubsub/subs")
+}
+
+pub(crate) fn name_resolve(req: &NameResolve) -> Request {
+    Request::url("/api/v0/name/resolve").args(req.args())
+}
+
+pub(crate) fn object_data(key: &str) -> Request {
+    let l = Query::new().arg(key);
+    Request::url("/api/v0/object/data").args(l)
+}
+
+pub(crate) fn object_diff(req: &ObjectDiff) -> Request {
+    Request::url("/api/v0/object/diff").args(req.args())
+}
+
+pub(crate) fn object_get(key: &str) -> Request {
+    let l = Query::new().arg(key);
+    Request::url("/api/v0/object/get").args(l)
+}
+
+pub(crate) fn object_links(req: &ObjectLinks) -> Request {
+    Request::url("/api/v0/object/links").args(req.args())
+}
+
+pub(crate) fn object_new(obj: &str) -> Request {
+    let l = Query::new().arg(obj);
+    Request::url("/api/v0/object/new").args(l)
+}
+
+pub(crate) fn object_patch_add_link(req: &ObjectPatchAddLink) -> Request {
+    Request::url("/api/v0/object/patch/add-link").args(req.args())
+}
+
+pub(crate) fn object_patch_append_data(hash: &str, file: Upload) -> Request {
+    let l = Query::new().arg(hash);
+    Request::url("/api/v0/object/patch/append-data").args(l).file(file)
+}
+
+pub(crate) fn object_patch_rm_link(hash: &str, iname: &str) -> Request {
+    let l = Query::new().arg(hash).arg(iname);
+    Request::url("/api/v0/object/patch/rm-link").args(l)
+}
+
+pub(crate) fn object_patch_set_data(hash: &str, file: Upload) -> Request {
+    let l = Query::new().arg(hash);
+    Request::url("/api/v0/object/patch/set-data").args(l).file(file)
+}
+
+pub(crate) fn object_put(file: Upload, req: &ObjectPut) -> Request {
+    Request::url("/api/v0/object/put").args(req.args()).file(file)
+}
+
+pub(crate) fn object_stat(key: &str) -> Request {
+    let l = Query::new().arg(key);
+    Request::url("/api/v0/object/stat").args(l)
+}
+
+pub(crate) fn pin_add(req: &PinAdd) -> Request {
+    Request::url("/api/v0/pin/add").args(req.args())
+}
+
+pub(crate) fn pin_add_progress(req: &PinAdd) -> Request {
+    let l = req.args().pair("progress", true);
+    Request::url("/api/v0/pin/add").args(l)
+}
+
+pub(crate) fn pin_ls(req: &PinLs) -> Request {
+    Request::url("/api/v0/pin/ls").args(req.args())
+}
+
+pub(crate) fn pin_remote_add(req: &PinRemoteAdd) -> Request {
+    Request::url("/api/v0/pin/remote/add").args(req.args())
+}
+
+pub(crate) fn pin_remote_ls(req: &PinRemoteLs) -> Request {
+    Request::url("/api/v0/pin/remote/ls").args(req.args())
+}
+
+pub(crate) fn pin_remote_rm(req: &PinRemoteRm) -> Request {
+    Request::url("/api/v0/pin/remote/rm").args(req.args())
+}
+
+pub(crate) fn pin_remote_service_add(service: &str, endpoint: &str, key: &str) -> Request {
+    let l = Query::new().arg(service).arg(endpoint).arg(key);
+    Request::url("/api/v0/pin/remote/service/add").args(l)
+}
+
+pub(crate) fn pin_remote_service_ls() -> Request {
+    Request::url("/api/v0/pin/remote/service/ls")
+}
+
+pub(crate) fn pin_remote_service_rm(service: &str) -> Request {
+    let l = Query::new().arg(service);
+    Request::url("/api/v0/pin/remote/service/rm").args(l)
+}
+
+pub(crate) fn pin_rm(req: &PinRm) -> Request {
+    Request::url("/api/v0/pin/rm").args(req.args())
+}
+
+pub(crate) fn pin_update(req: &PinUpdate) -> Request {
+    Request::url("/api/v0/pin/update").args(req.args())
+}
+
+pub(crate) fn pin_verify(req: &PinVerify) -> Request {
+    Request::url("/api/v0/pin/verify").args(req.args())
+}
+
+pub(crate) fn ping(req: &Ping) -> Request {
+    Request::url("/api/v0/ping").args(req.args())
+}
+
+pub(crate) fn pubsub_ls() -> Request {
+    Request::url("/api/v0/pubsub/ls")
+}
+
+pub(crate) fn pubsub_peers(topic: &str, encoding: PubsubEncoding) -> Request {
+    let l = Query::new().arg(pubsub_topic(topic, encoding));
+    Request::url("/api/v0/pubsub/peers").args(l)
+}
+
+pub(crate) fn pubsub_pub(topic: &str, data: Upload, encoding: PubsubEncoding) -> Request {
+    let l = Query::new().arg(pubsub_topic(topic, encoding));
+    Request::url("/api/v0/pubsub/pub").args(l).file(data)
+}
+
+pub(crate) fn pubsub_sub(topic: &str, discover: bool, encoding: PubsubEncoding) -> Request {
+    let l = Query::new().arg(pubsub_topic(topic, encoding)).pair("discover", discover);
+    Request::url("/api/v0/pubsub/sub").args(l)
+}
+
+/// Daemons before Kubo 0.11 take topic names as they are, later ones multibase-encoded.
+fn pubsub_topic(topic: &str, encoding: PubsubEncoding) -> String {
+    match encoding {
+        PubsubEncoding::Base64 => topic.to_string(),
+        PubsubEncoding::Multibase => multibase::encode(topic.as_bytes()),
+    }
+}
+
+pub(crate) fn refs_local() -> Request {
+    Request::url("/api/v0/refs/local")
+}
+
+pub(crate) fn repo_fsck() -> Request {
+    Request::url("/api/v0/repo/fsck")
+}
+
+pub(crate) fn repo_gc(req: &RepoGc) -> Request {
+    Request::url("/api/v0/repo/gc").args(req.args())
+}
+
+pub(crate) fn repo_stat(req: &RepoStat) -> Request {
+    Request::url("/api/v0/repo/stat").args(req.args())
+}
+
+pub(crate) fn repo_verify() -> Request {
+    Request::url("/api/v0/repo/verify")
+}
+
+pub(crate) fn repo_version(req: &RepoVersion) -> Request {
+    Request::url("/api/v0/repo/version").args(req.args())
+}
+
+pub(crate) fn resolve(req: &Resolve) -> Request {
+    Request::url("/api/v0/resolve").args(req.args())
+}
+
+pub(crate) fn routing_findpeer(peer: &str) -> Request {
+    let l = Query::new().arg(peer);
+    Request::url("/api/v0/routing/findpeer").args(l)
+}
+
+pub(crate) fn routing_findprovs(key: &str, num_providers: Option<u32>) -> Request {
+    let l = Query::new().arg(key).opt("num-providers", num_providers);
+    Request::url("/api/v0/routing/findprovs").args(l)
+}
+
+pub(crate) fn routing_get(key: &str) -> Request {
+    let l = Query::new().arg(key);
+    Request::url("/api/v0/routing/get").args(l)
+}
+
+pub(crate) fn routing_provide(req: &DhtProvide) -> Request {
+    Request::url("/api/v0/routing/provide").args(req.args())
+}
+
+pub(crate) fn routing_put(key: &str, value: Upload) -> Request {
+    let l = Query::new().arg(key);
+    Request::url("/api/v0/routing/put").args(l).file(value)
+}
+
+pub(crate) fn shutdown() -> Request {
+    Request::url("/api/v0/shutdown")
+}
+
+pub(crate) fn stats_bitswap() -> Request {
+    Request::url("/api/v0/stats/bitswap")
+}
+
+pub(crate) fn stats_bw(req: &StatsBw) -> Request {
+    Request::url("/api/v0/stats/bw").args(req.args())
+}
+
+pub(crate) fn stats_bw_poll(req: &StatsBw, interval: Duration) -> Request {
+    let l = req.args().pair("poll", true).pair("interval", format!("{}ms", interval.as_millis()));
+    Request::url("/api/v0/stats/bw").args(l)
+}
+
+pub(crate) fn stats_repo(req: &RepoStat) -> Request {
+    Request::url("/api/v0/stats/repo").args(req.args())
+}
+
+pub(crate) fn swarm_addrs_local(peer: &str) -> Request {
+    let l = Query::new().arg(peer);
+    Request::url("/api/v0/swarm/addrs/local").args(l)
+}
+
+pub(crate) fn swarm_connect(addrs: &str) -> Request {
+    let l = Query::new().arg(addrs);
+    Request::url("/api/v0/swarm/connect").args(l)
+}
+
+pub(crate) fn swarm_disconnect(addrs: &str) -> Request {
+    let l = Query::new().arg(addrs);
+    Request::url("/api/v0/swarm/disconnect").args(l)
+}
+
+pub(crate) fn swarm_filters_add(maddr: &str) -> Request {
+    let l = Query::new().arg(maddr);
+    Request::url("/api/v0/swarm/filters/add").args(l)
+}
+
+pub(crate) fn swarm_filderst_rm(maddr: &str) -> Request {
+    let l = Query::new().arg(maddr);
+    Request::url("/api/v0/swarm/filters/rm").args(l)
+}
+
+pub(crate) fn swarm_peering_add(addr: &str) -> Request {
+    let l = Query::new().arg(addr);
+    Request::url("/api/v0/swarm/peering/add").args(l)
+}
+
+pub(crate) fn swarm_peering_ls() -> Request {
+    Request::url("/api/v0/swarm/peering/ls")
+}
+
+pub(crate) fn swarm_peering_rm(peer: &str) -> Request {
+    let l = Query::new().arg(peer);
+    Request::url("/api/v0/swarm/peering/rm").args(l)
+}
+
+pub(crate) fn swarm_peers() -> Request {
+    Request::url("/api/v0/swarm/peers")
+}
+
+pub(crate) fn tar_add(file: Upload) -> Request {
+    Request::url("/api/v0/tar/add").file(file)
+}
+
+pub(crate) fn tar_cat(ipath: &str) -> Request {
+    let l = Query::new().arg(ipath);
+    Request::url("/api/v0/tar/cat").args(l)
+}
+
+pub(crate) fn tour_list() -> Request {
+    Request::url("/api/v0/tour/list")
+}
+
+pub(crate) fn tour_next() -> Request {
+    Request::url("/api/v0/tour/next")
+}
+
+pub(crate) fn tour_restart() -> Request {
+    Request::url("/api/v0/tour/restart")
+}
+
+pub(crate) fn update(sargs: &str) -> Request {
+    let l = Query::new().arg(sargs);
+    Request::url("/api/v0/update").args(l)
+}
+
+pub(crate) fn version(req: &Version) -> Request {
+    Request::url("/api/v0/version").args(req.args())
+}
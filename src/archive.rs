@@ -0,0 +1,209 @@
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Component, Path, PathBuf};
+
+use flate2::read::GzDecoder;
+use tar::{Archive, EntryType};
+
+/// Unpacks the tar archive `get` receives into `out`, the way `ipfs get -o out` does: the
+/// archive's root entry, named after the fetched path, becomes `out` itself.
+///
+/// Entries that would land outside `out`, whether through `..`, absolute paths, a symlinked root
+/// or symlinks unpacked earlier, fail the whole extraction.
+pub(crate) fn extract<R: Read>(archive: R, out: &Path, gzipped: bool) -> io::Result<()> {
+    if gzipped {
+        unpack(GzDecoder::new(archive), out)
+    } else {
+        unpack(archive, out)
+    }
+}
+
+fn unpack<R: Read>(archive: R, out: &Path) -> io::Result<()> {
+    let root = trusted_root(out)?;
+    let mut archive = Archive::new(archive);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let relative = strip_root(&path)?;
+        let entry_type = entry.header().entry_type();
+        let target = if relative.as_os_str().is_empty() {
+            // Every later entry would land wherever a symlinked root points.
+            if entry_type == EntryType::Symlink {
+                return Err(escapes(&path));
+            }
+            // Joining an empty path would add a trailing slash, which a file cannot be written to.
+            root.clone()
+        } else {
+            root.join(&relative)
+        };
+        check_inside(&root, &target)?;
+        match entry_type {
+            EntryType::Directory => fs::create_dir_all(&target)?,
+            EntryType::Regular | EntryType::Symlink => {
+                if let Some(parent) = target.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                entry.unpack(&target)?;
+            }
+            // UnixFS only produces the kinds above.
+            _ => continue,
+        }
+    }
+    Ok(())
+}
+
+/// `out` with the symlinks above it resolved, which nothing in the archive may escape.
+fn trusted_root(out: &Path) -> io::Result<PathBuf> {
+    let name = out.file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, format!("{} is not a file name to write to", out.display()))
+    })?;
+    let parent = match out.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    fs::create_dir_all(parent)?;
+    Ok(fs::canonicalize(parent)?.join(name))
+}
+
+/// Drops the first component of an entry path, refusing anything that is not a plain name.
+fn strip_root(path: &Path) -> io::Result<PathBuf> {
+    let mut components = path.components();
+    components.next();
+    let mut relative = PathBuf::new();
+    for component in components {
+        match component {
+            Component::Normal(name) => relative.push(name),
+            Component::CurDir => {}
+            _ => return Err(escapes(path)),
+        }
+    }
+    Ok(relative)
+}
+
+/// Makes sure `target`, once the symlinks already on disk are resolved, is still inside `root`.
+fn check_inside(root: &Path, target: &Path) -> io::Result<()> {
+    let mut existing = target;
+    // Paths that do not exist yet cannot be symlinks; check what is already there.
+    while fs::symlink_metadata(existing).is_err() {
+        existing = match existing.parent() {
+            Some(parent) if parent.starts_with(root) => parent,
+            _ => return Ok(()),
+        };
+    }
+    match fs::canonicalize(existing) {
+        Ok(resolved) if resolved.starts_with(root) => Ok(()),
+        // Dangling symlinks point nowhere we can vouch for either.
+        _ => Err(escapes(target)),
+    }
+}
+
+fn escapes(path: &Path) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, format!("archive entry {} escapes the output path", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use tar::{Builder, Header};
+
+    fn scratch(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ipfs-api-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn append(builder: &mut Builder<Vec<u8>>, path: &str, entry_type: EntryType, data: &[u8]) {
+        let mut header = Header::new_gnu();
+        // Written by hand so that hostile names get through.
+        header.as_old_mut().name[..path.len()].copy_from_slice(path.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_size(data.len() as u64);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append(&header, data).unwrap();
+    }
+
+    #[test]
+    fn root_entry_becomes_the_output_path() {
+        let mut builder = Builder::new(Vec::new());
+        append(&mut builder, "QmRoot", EntryType::Directory, b"");
+        append(&mut builder, "QmRoot/sub", EntryType::Directory, b"");
+        append(&mut builder, "QmRoot/sub/a.txt", EntryType::Regular, b"hello");
+        let out = scratch("extract");
+        extract(&builder.into_inner().unwrap()[..], &out, false).unwrap();
+        assert_eq!("hello", fs::read_to_string(out.join("sub/a.txt")).unwrap());
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[test]
+    fn single_files_are_written_to_the_output_path() {
+        let mut builder = Builder::new(Vec::new());
+        append(&mut builder, "QmFile", EntryType::Regular, b"hello");
+        let out = scratch("single");
+        extract(&builder.into_inner().unwrap()[..], &out, false).unwrap();
+        assert_eq!("hello", fs::read_to_string(&out).unwrap());
+        fs::remove_file(&out).unwrap();
+    }
+
+    #[test]
+    fn gzipped_archives_are_unpacked() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+        let mut builder = Builder::new(Vec::new());
+        append(&mut builder, "QmFile", EntryType::Regular, b"hello");
+        let mut gz = GzEncoder::new(Vec::new(), Compression::default());
+        gz.write_all(&builder.into_inner().unwrap()).unwrap();
+        let out = scratch("gzip");
+        extract(&gz.finish().unwrap()[..], &out, true).unwrap();
+        assert_eq!("hello", fs::read_to_string(&out).unwrap());
+        fs::remove_file(&out).unwrap();
+    }
+
+    #[test]
+    fn traversal_is_refused() {
+        let mut builder = Builder::new(Vec::new());
+        append(&mut builder, "QmRoot", EntryType::Directory, b"");
+        append(&mut builder, "QmRoot/../../evil.txt", EntryType::Regular, b"gotcha");
+        let out = scratch("traversal");
+        let err = extract(&builder.into_inner().unwrap()[..], &out, false).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writing_through_symlinks_is_refused() {
+        let mut builder = Builder::new(Vec::new());
+        append(&mut builder, "QmRoot", EntryType::Directory, b"");
+        let mut link = Header::new_gnu();
+        link.set_entry_type(EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "QmRoot/link", "/tmp").unwrap();
+        append(&mut builder, "QmRoot/link/evil.txt", EntryType::Regular, b"gotcha");
+        let out = scratch("symlink");
+        let err = extract(&builder.into_inner().unwrap()[..], &out, false).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        fs::remove_dir_all(&out).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinked_root_entries_are_refused() {
+        let elsewhere = scratch("elsewhere");
+        fs::create_dir_all(&elsewhere).unwrap();
+        let mut builder = Builder::new(Vec::new());
+        let mut link = Header::new_gnu();
+        link.set_entry_type(EntryType::Symlink);
+        link.set_size(0);
+        builder.append_link(&mut link, "QmRoot", &elsewhere).unwrap();
+        append(&mut builder, "QmRoot/pwned.txt", EntryType::Regular, b"gotcha");
+        let out = scratch("root-symlink");
+        let err = extract(&builder.into_inner().unwrap()[..], &out, false).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        assert!(!elsewhere.join("pwned.txt").exists());
+        assert!(fs::symlink_metadata(&out).is_err());
+        fs::remove_dir_all(&elsewhere).unwrap();
+    }
+}
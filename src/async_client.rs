@@ -0,0 +1,1553 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::pin;
+use std::sync::Arc;
+use std::thread;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::channel::{mpsc, oneshot};
+use futures::executor;
+use futures::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, BufReader, Lines};
+use futures::sink::SinkExt;
+use futures::stream::{Stream, StreamExt, TryStreamExt};
+use isahc::{AsyncBody, AsyncReadResponseExt, Response};
+use json::JsonValue;
+
+use crate::add::AddOptions;
+use crate::api::{self, Request};
+use crate::archive;
+use crate::capabilities::{CapabilityCache, DaemonCapabilities, Fetched};
+use crate::commands::CommandTree;
+use crate::endpoint::Endpoint;
+use crate::error::{IpfsError, Result};
+use crate::files::FilesWriteOptions;
+use crate::multipart::{MultipartReader, Upload};
+use crate::params::*;
+use crate::response::{self, *};
+
+/// Size of the chunks a `ResponseStream` yields.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A daemon response delivered as a stream of chunks while it downloads.
+pub struct ResponseStream {
+    body: AsyncBody,
+    buf: Vec<u8>,
+}
+
+impl Stream for ResponseStream {
+    type Item = Result<Vec<u8>>;
+
+    fn poll_next(self: pin::Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<Vec<u8>>>> {
+        let this = self.get_mut();
+        match pin::Pin::new(&mut this.body).poll_read(cx, &mut this.buf) {
+            Poll::Ready(Ok(0)) => Poll::Ready(None),
+            Poll::Ready(Ok(n)) => Poll::Ready(Some(Ok(this.buf[..n].to_vec()))),
+            Poll::Ready(Err(e)) => Poll::Ready(Some(Err(IpfsError::Io(e)))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// A newline-delimited JSON response, decoded one object at a time as the daemon sends it.
+pub struct JsonLinesStream<T> {
+    lines: Lines<BufReader<AsyncBody>>,
+    decode: fn(&[u8]) -> Result<T>,
+}
+
+impl<T> Stream for JsonLinesStream<T> {
+    type Item = Result<T>;
+
+    fn poll_next(self: pin::Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<T>>> {
+        let this = self.get_mut();
+        loop {
+            return match pin::Pin::new(&mut this.lines).poll_next(cx) {
+                Poll::Ready(Some(Ok(ref line))) if line.trim().is_empty() => continue,
+                Poll::Ready(Some(Ok(line))) => Poll::Ready(Some((this.decode)(line.as_bytes()))),
+                Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(IpfsError::Io(e)))),
+                Poll::Ready(None) => Poll::Ready(None),
+                Poll::Pending => Poll::Pending,
+            };
+        }
+    }
+}
+
+/// How many chunks may wait in a pipe between the executor and a blocking thread.
+const READ_AHEAD: usize = 4;
+
+/// Adapts an upload body for isahc.
+///
+/// Files and caller readers block, so the body is read on its own thread and handed over
+/// through a channel, which keeps the executor free while an upload is produced.
+fn upload_body(mut reader: MultipartReader) -> AsyncBody {
+    let (mut tx, rx) = mpsc::channel::<io::Result<Vec<u8>>>(READ_AHEAD);
+    thread::spawn(move || loop {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let item = match reader.read(&mut chunk) {
+            Ok(0) => return,
+            Ok(n) => {
+                chunk.truncate(n);
+                Ok(chunk)
+            }
+            Err(e) => Err(e),
+        };
+        let failed = item.is_err();
+        // The receiver is gone once isahc drops the request, so there is no one left to feed.
+        if executor::block_on(tx.send(item)).is_err() || failed {
+            return;
+        }
+    });
+    AsyncBody::from_reader(rx.into_async_read())
+}
+
+/// Reads an async source from a blocking thread.
+struct BlockingRead<R>(R);
+
+impl<R: AsyncRead + Unpin> Read for BlockingRead<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        executor::block_on(self.0.read(buf))
+    }
+}
+
+/// Non-blocking counterpart of `IPFS`.
+///
+/// Every endpoint returns a future instead of blocking the calling thread, so many calls can be
+/// in flight at once on a single executor thread. Connections are pooled and reused by isahc.
+#[derive(Clone, Default)]
+pub struct AsyncIpfs {
+    endpoint: Endpoint,
+    capabilities: CapabilityCache,
+}
+
+impl AsyncIpfs {
+
+    pub fn new() -> AsyncIpfs {
+        Default::default()
+    }
+
+    /// A client for the daemon at `addr`, a multiaddr such as `/ip4/127.0.0.1/tcp/5001` or a URL.
+    pub fn connect(addr: &str) -> Result<AsyncIpfs> {
+        let mut ipfs = AsyncIpfs::new();
+        ipfs.endpoint(Endpoint::parse(addr)?);
+        Ok(ipfs)
+    }
+
+    /// A client for the local daemon, found as by `Endpoint::discover`.
+    pub fn discover() -> Result<AsyncIpfs> {
+        let mut ipfs = AsyncIpfs::new();
+        ipfs.endpoint(Endpoint::discover()?);
+        Ok(ipfs)
+    }
+
+    /// Shorthand for an `endpoint` at `inp`, e.g. `http://localhost`, and `port`.
+    pub fn host(&mut self, inp: &str, port: u16) -> &mut AsyncIpfs {
+        self.endpoint(Endpoint::new(format!("{}:{}", inp, port)))
+    }
+
+    pub fn endpoint(&mut self, inp: Endpoint) -> &mut AsyncIpfs {
+        self.endpoint = inp;
+        // Another daemon may serve other commands.
+        self.capabilities = Default::default();
+        self
+    }
+
+    /// The daemon's version and commands, fetched on first use and cached from then on.
+    ///
+    /// If they could not be fetched, calls are sent unchecked; asking here fetches them again
+    /// and says why that fails.
+    pub async fn capabilities(&self) -> Result<DaemonCapabilities> {
+        match self.capabilities.get() {
+            Some(Fetched::Known(caps)) => Ok((*caps).clone()),
+            _ => self.fetch_capabilities().await.map(|caps| (*caps).clone()),
+        }
+    }
+
+    //--------------------------------------- API FUNCTIONS ---------------------------------------
+
+
+    pub async fn add<U: Into<Upload>>(&self, file: U) -> Result<AddResponse> {
+        self.add_raw(file).await.and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub async fn add_raw<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::add(file.into())).await
+    }
+
+    /// Like `add`, with the daemon options set in `options`.
+    pub async fn add_with<U: Into<Upload>>(&self, file: U, options: &AddOptions) -> Result<AddResponse> {
+        self.add_with_raw(file, options).await.and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub async fn add_with_raw<U: Into<Upload>>(&self, file: U, options: &AddOptions) -> Result<Vec<u8>> {
+        self.query(api::add_with(file.into(), options)).await
+    }
+
+    /// Like `add_with`, but reports how much of the upload the daemon has received as it goes,
+    /// ending with the added entry.
+    pub async fn add_progress<U: Into<Upload>>(&self, file: U, options: &AddOptions) -> Result<JsonLinesStream<AddEvent>> {
+        self.query_lines(api::add_progress(file.into(), options)).await
+    }
+
+    pub async fn add_path<P: AsRef<Path>>(&self, path: P, options: &AddOptions) -> Result<AddPathResponse> {
+        self.add_path_raw(path, options).await.and_then(|r| response::decode_add_path(&r))
+    }
+
+    pub async fn add_path_raw<P: AsRef<Path>>(&self, path: P, options: &AddOptions) -> Result<Vec<u8>> {
+        self.query(api::add_path(path.as_ref(), options)?).await
+    }
+
+    /// Like `add_path`, but reports the daemon's progress through every file as it goes,
+    /// followed by the added entries.
+    pub async fn add_path_progress<P: AsRef<Path>>(&self, path: P, options: &AddOptions) -> Result<JsonLinesStream<AddEvent>> {
+        self.query_lines(api::add_path_progress(path.as_ref(), options)?).await
+    }
+
+    pub async fn bitswap_ledger(&self, peer: &str) -> Result<BitswapLedgerResponse> {
+        self.bitswap_ledger_raw(peer).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn bitswap_ledger_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::bitswap_ledger(peer)).await
+    }
+
+    pub async fn bitswap_stat(&self) -> Result<BitswapStatResponse> {
+        self.bitswap_stat_raw().await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn bitswap_stat_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bitswap_stat()).await
+    }
+
+    pub async fn bitswap_unwant(&self, key: &str) -> Result<()> {
+        self.bitswap_unwant_raw(key).await.map(|_| ())
+    }
+
+    pub async fn bitswap_unwant_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::bitswap_unwant(key)).await
+    }
+
+    pub async fn bitswap_wantlist(&self, peer: &str) -> Result<Vec<String>> {
+        self.bitswap_wantlist_raw(peer).await.and_then(|r| response::decode_cids(&r, "Keys"))
+    }
+
+    pub async fn bitswap_wantlist_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::bitswap_wantlist(peer)).await
+    }
+
+    pub async fn block_get(&self, hash: &str) -> Result<Vec<u8>> {
+        self.query(api::block_get(hash)).await
+    }
+
+    /// Like `block_get`, but yields the block in chunks as it arrives instead of buffering it.
+    pub async fn block_get_stream(&self, hash: &str) -> Result<ResponseStream> {
+        self.query_stream(api::block_get(hash)).await
+    }
+
+    /// Like `block_get`, but copies the block straight into `out`, returning the number of bytes written.
+    pub async fn block_get_to<W: AsyncWrite + Unpin>(&self, hash: &str, out: &mut W) -> Result<u64> {
+        self.query_to(api::block_get(hash), out).await
+    }
+
+    pub async fn block_put<U: Into<Upload>>(&self, file: U, req: &BlockPut) -> Result<BlockStatResponse> {
+        self.block_put_raw(file, req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn block_put_raw<U: Into<Upload>>(&self, file: U, req: &BlockPut) -> Result<Vec<u8>> {
+        self.query(api::block_put(file.into(), req)).await
+    }
+
+    pub async fn block_rm(&self, req: &BlockRm) -> Result<Vec<BlockRmResponse>> {
+        self.block_rm_raw(req).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn block_rm_raw(&self, req: &BlockRm) -> Result<Vec<u8>> {
+        self.query(api::block_rm(req)).await
+    }
+
+    pub async fn block_stat(&self, hash: &str) -> Result<BlockStatResponse> {
+        self.block_stat_raw(hash).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn block_stat_raw(&self, hash: &str) -> Result<Vec<u8>> {
+        self.query(api::block_stat(hash)).await
+    }
+
+    pub async fn bootstrap_add_default(&self) -> Result<Vec<String>> {
+        self.bootstrap_add_default_raw().await.and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub async fn bootstrap_add_default_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_add_default()).await
+    }
+
+    pub async fn bootstrap_add(&self, peers: &[&str]) -> Result<Vec<String>> {
+        self.bootstrap_add_raw(peers).await.and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub async fn bootstrap_add_raw(&self, peers: &[&str]) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_add(peers)).await
+    }
+
+    pub async fn bootstrap_list(&self) -> Result<Vec<String>> {
+        self.bootstrap_list_raw().await.and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub async fn bootstrap_list_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_list()).await
+    }
+
+    pub async fn bootstrap_rm_all(&self) -> Result<Vec<String>> {
+        self.bootstrap_rm_all_raw().await.and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub async fn bootstrap_rm_all_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_rm_all()).await
+    }
+
+    pub async fn bootstrap_rm(&self, peers: &[&str]) -> Result<Vec<String>> {
+        self.bootstrap_rm_raw(peers).await.and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub async fn bootstrap_rm_raw(&self, peers: &[&str]) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_rm(peers)).await
+    }
+
+    pub async fn cat(&self, hash: &str) -> Result<Vec<u8>> {
+        self.query(api::cat(hash)).await
+    }
+
+    /// Like `cat`, but yields the content in chunks as it arrives instead of buffering it.
+    pub async fn cat_stream(&self, hash: &str) -> Result<ResponseStream> {
+        self.query_stream(api::cat(hash)).await
+    }
+
+    /// Like `cat`, but copies the content straight into `out`, returning the number of bytes written.
+    pub async fn cat_to<W: AsyncWrite + Unpin>(&self, hash: &str, out: &mut W) -> Result<u64> {
+        self.query_to(api::cat(hash), out).await
+    }
+
+    pub async fn cid_base32(&self, cid: &str) -> Result<CidFormatResponse> {
+        self.cid_base32_raw(cid).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn cid_base32_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::cid_base32(cid)).await
+    }
+
+    pub async fn cid_bases(&self) -> Result<Vec<CodeAndName>> {
+        self.cid_bases_raw().await.and_then(|r| response::decode_array(&r))
+    }
+
+    pub async fn cid_bases_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::cid_bases()).await
+    }
+
+    pub async fn cid_codecs(&self) -> Result<Vec<CodeAndName>> {
+        self.cid_codecs_raw().await.and_then(|r| response::decode_array(&r))
+    }
+
+    pub async fn cid_codecs_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::cid_codecs()).await
+    }
+
+    pub async fn cid_format(&self, req: &CidFormat) -> Result<CidFormatResponse> {
+        self.cid_format_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn cid_format_raw(&self, req: &CidFormat) -> Result<Vec<u8>> {
+        self.query(api::cid_format(req)).await
+    }
+
+    pub async fn cid_hashes(&self) -> Result<Vec<CodeAndName>> {
+        self.cid_hashes_raw().await.and_then(|r| response::decode_array(&r))
+    }
+
+    pub async fn cid_hashes_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::cid_hashes()).await
+    }
+
+    pub async fn commands(&self) -> Result<CommandTree> {
+        self.commands_raw().await.and_then(|r| response::decode(&r).map(CommandTree::new))
+    }
+
+    pub async fn commands_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::commands()).await
+    }
+
+    pub async fn config_replace<U: Into<Upload>>(&self, file: U) -> Result<()> {
+        self.config_replace_raw(file).await.map(|_| ())
+    }
+
+    pub async fn config_replace_raw<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::config_replace(file.into())).await
+    }
+
+    pub async fn config_show(&self) -> Result<JsonValue> {
+        self.config_show_raw().await.and_then(|r| response::parse(&r))
+    }
+
+    pub async fn config_show_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::config_show()).await
+    }
+
+    /// Returns the CAR file of the DAG below `cid`.
+    pub async fn dag_export(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_export(cid)).await
+    }
+
+    /// Like `dag_export`, but yields the content in chunks as it arrives instead of buffering it.
+    pub async fn dag_export_stream(&self, cid: &str) -> Result<ResponseStream> {
+        self.query_stream(api::dag_export(cid)).await
+    }
+
+    /// Like `dag_export`, but copies the content straight into `out`, returning the number of bytes written.
+    pub async fn dag_export_to<W: AsyncWrite + Unpin>(&self, cid: &str, out: &mut W) -> Result<u64> {
+        self.query_to(api::dag_export(cid), out).await
+    }
+
+    pub async fn dag_get(&self, inp: &str) -> Result<JsonValue> {
+        self.dag_get_raw(inp).await.and_then(|r| response::parse(&r))
+    }
+
+    pub async fn dag_get_raw(&self, inp: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_get(inp)).await
+    }
+
+    /// Imports the blocks of a CAR file, returning its roots.
+    pub async fn dag_import<U: Into<Upload>>(&self, file: U, req: &DagImport) -> Result<Vec<DagImportRoot>> {
+        self.dag_import_raw(file, req).await.and_then(|r| response::decode_dag_import(&r))
+    }
+
+    pub async fn dag_import_raw<U: Into<Upload>>(&self, file: U, req: &DagImport) -> Result<Vec<u8>> {
+        self.query(api::dag_import(file.into(), req)).await
+    }
+
+    pub async fn dag_put<U: Into<Upload>>(&self, file: U, req: &DagPut) -> Result<String> {
+        self.dag_put_raw(file, req).await.and_then(|r| response::decode_cid(&r, "Cid"))
+    }
+
+    pub async fn dag_put_raw<U: Into<Upload>>(&self, file: U, req: &DagPut) -> Result<Vec<u8>> {
+        self.query(api::dag_put(file.into(), req)).await
+    }
+
+    pub async fn dag_resolve(&self, ipath: &str) -> Result<DagResolveResponse> {
+        self.dag_resolve_raw(ipath).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn dag_resolve_raw(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_resolve(ipath)).await
+    }
+
+    /// The size and block count of the DAG under `cid`. The daemon reports its progress along
+    /// the way; this returns the final count.
+    pub async fn dag_stat(&self, cid: &str) -> Result<DagStatResponse> {
+        self.dag_stat_raw(cid).await.and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub async fn dag_stat_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_stat(cid)).await
+    }
+
+    /// Yields the query's events as the daemon reports them; dropping it stops the query.
+    /// Yields the query's events as the daemon reports them; dropping it stops the query.
+    pub async fn dht_findpeer(&self, req: &DhtQuery) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::dht_findpeer(req)).await
+    }
+
+    pub async fn dht_findpeer_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_findpeer(req)).await
+    }
+
+    pub async fn dht_findprovs(&self, req: &DhtQuery) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::dht_findprovs(req)).await
+    }
+
+    pub async fn dht_findprovs_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_findprovs(req)).await
+    }
+
+    pub async fn dht_get(&self, req: &DhtQuery) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::dht_get(req)).await
+    }
+
+    pub async fn dht_get_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_get(req)).await
+    }
+
+    pub async fn dht_provide(&self, req: &DhtProvide) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::dht_provide(req)).await
+    }
+
+    pub async fn dht_provide_raw(&self, req: &DhtProvide) -> Result<Vec<u8>> {
+        self.query(api::dht_provide(req)).await
+    }
+
+    pub async fn dht_put(&self, req: &DhtPut) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::dht_put(req)).await
+    }
+
+    pub async fn dht_put_raw(&self, req: &DhtPut) -> Result<Vec<u8>> {
+        self.query(api::dht_put(req)).await
+    }
+
+    pub async fn dht_query(&self, req: &DhtQuery) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::dht_query(req)).await
+    }
+
+    pub async fn dht_query_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_query(req)).await
+    }
+
+    pub async fn diag_cmds_clear(&self) -> Result<()> {
+        self.diag_cmds_clear_raw().await.map(|_| ())
+    }
+
+    pub async fn diag_cmds_clear_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::diag_cmds_clear()).await
+    }
+
+    /// Returns a zip of the daemon's profiles, collected over `profile_time`.
+    pub async fn diag_profile(&self, profile_time: Duration) -> Result<Vec<u8>> {
+        self.query(api::diag_profile(profile_time)).await
+    }
+
+    /// Like `diag_profile`, but yields the content in chunks as it arrives instead of buffering it.
+    pub async fn diag_profile_stream(&self, profile_time: Duration) -> Result<ResponseStream> {
+        self.query_stream(api::diag_profile(profile_time)).await
+    }
+
+    /// Like `diag_profile`, but copies the content straight into `out`, returning the number of bytes written.
+    pub async fn diag_profile_to<W: AsyncWrite + Unpin>(&self, profile_time: Duration, out: &mut W) -> Result<u64> {
+        self.query_to(api::diag_profile(profile_time), out).await
+    }
+
+    pub async fn diag_set_time(&self, ntime: &str) -> Result<()> {
+        self.diag_set_time_raw(ntime).await.map(|_| ())
+    }
+
+    pub async fn diag_set_time_raw(&self, ntime: &str) -> Result<Vec<u8>> {
+        self.query(api::diag_set_time(ntime)).await
+    }
+
+    pub async fn diag_net(&self, req: &DiagNet) -> Result<Vec<u8>> {
+        self.query(api::diag_net(req)).await
+    }
+
+    pub async fn diag_sys(&self) -> Result<JsonValue> {
+        self.diag_sys_raw().await.and_then(|r| response::parse(&r))
+    }
+
+    pub async fn diag_sys_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::diag_sys()).await
+    }
+
+    pub async fn dns(&self, req: &Dns) -> Result<String> {
+        self.dns_raw(req).await.and_then(|r| response::decode_string(&r, "Path"))
+    }
+
+    pub async fn dns_raw(&self, req: &Dns) -> Result<Vec<u8>> {
+        self.query(api::dns(req)).await
+    }
+
+    pub async fn file_ls(&self, ipath: &str) -> Result<FileLsResponse> {
+        self.file_ls_raw(ipath).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn file_ls_raw(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::file_ls(ipath)).await
+    }
+
+    pub async fn files_chcid(&self, ipath: &str, cid_version: Option<u32>, hash: Option<&str>) -> Result<()> {
+        self.files_chcid_raw(ipath, cid_version, hash).await.map(|_| ())
+    }
+
+    pub async fn files_chcid_raw(&self, ipath: &str, cid_version: Option<u32>, hash: Option<&str>) -> Result<Vec<u8>> {
+        self.query(api::files_chcid(ipath, cid_version, hash)).await
+    }
+
+    pub async fn files_cp(&self, from: &str, to: &str) -> Result<()> {
+        self.files_cp_raw(from, to).await.map(|_| ())
+    }
+
+    pub async fn files_cp_raw(&self, from: &str, to: &str) -> Result<Vec<u8>> {
+        self.query(api::files_cp(from, to)).await
+    }
+
+    pub async fn files_flush(&self, ipath: &str) -> Result<()> {
+        self.files_flush_raw(ipath).await.map(|_| ())
+    }
+
+    pub async fn files_flush_raw(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::files_flush(ipath)).await
+    }
+
+    pub async fn files_ls(&self, req: &FilesLs) -> Result<Vec<FilesEntry>> {
+        self.files_ls_raw(req).await.and_then(|r| response::decode_list(&r, "Entries"))
+    }
+
+    pub async fn files_ls_raw(&self, req: &FilesLs) -> Result<Vec<u8>> {
+        self.query(api::files_ls(req)).await
+    }
+
+    pub async fn files_mkdir(&self, req: &FilesMkdir) -> Result<()> {
+        self.files_mkdir_raw(req).await.map(|_| ())
+    }
+
+    pub async fn files_mkdir_raw(&self, req: &FilesMkdir) -> Result<Vec<u8>> {
+        self.query(api::files_mkdir(req)).await
+    }
+
+    pub async fn files_mv(&self, source: &str, dest: &str) -> Result<()> {
+        self.files_mv_raw(source, dest).await.map(|_| ())
+    }
+
+    pub async fn files_mv_raw(&self, source: &str, dest: &str) -> Result<Vec<u8>> {
+        self.query(api::files_mv(source, dest)).await
+    }
+
+    pub async fn files_read(&self, req: &FilesRead) -> Result<Vec<u8>> {
+        self.query(api::files_read(req)).await
+    }
+
+    /// Like `files_read`, but yields the file content in chunks as it arrives instead of buffering it.
+    pub async fn files_read_stream(&self, req: &FilesRead) -> Result<ResponseStream> {
+        self.query_stream(api::files_read(req)).await
+    }
+
+    /// Like `files_read`, but copies the file content straight into `out`, returning the number of bytes written.
+    pub async fn files_read_to<W: AsyncWrite + Unpin>(&self, req: &FilesRead, out: &mut W) -> Result<u64> {
+        self.query_to(api::files_read(req), out).await
+    }
+
+    pub async fn files_rm(&self, req: &FilesRm) -> Result<()> {
+        self.files_rm_raw(req).await.map(|_| ())
+    }
+
+    pub async fn files_rm_raw(&self, req: &FilesRm) -> Result<Vec<u8>> {
+        self.query(api::files_rm(req)).await
+    }
+
+    pub async fn files_stat(&self, req: &FilesStat) -> Result<FilesStatResponse> {
+        self.files_stat_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn files_stat_raw(&self, req: &FilesStat) -> Result<Vec<u8>> {
+        self.query(api::files_stat(req)).await
+    }
+
+    /// Writes `data` into the MFS file at `ipath`. Readers are streamed, so large writes are not
+    /// buffered in memory.
+    pub async fn files_write<U: Into<Upload>>(&self, ipath: &str, data: U, options: &FilesWriteOptions) -> Result<()> {
+        self.files_write_raw(ipath, data, options).await.map(|_| ())
+    }
+
+    pub async fn files_write_raw<U: Into<Upload>>(&self, ipath: &str, data: U, options: &FilesWriteOptions) -> Result<Vec<u8>> {
+        self.query(api::files_write(ipath, data.into(), options)).await
+    }
+
+    pub async fn filestore_dups(&self) -> Result<Vec<RefResponse>> {
+        self.filestore_dups_raw().await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn filestore_dups_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::filestore_dups()).await
+    }
+
+    pub async fn filestore_ls(&self, cid: &str) -> Result<Vec<FilestoreObject>> {
+        self.filestore_ls_raw(cid).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn filestore_ls_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::filestore_ls(cid)).await
+    }
+
+    pub async fn filestore_verify(&self, cid: &str) -> Result<Vec<FilestoreObject>> {
+        self.filestore_verify_raw(cid).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn filestore_verify_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::filestore_verify(cid)).await
+    }
+
+    /// Fetches the requested path into the local path `out`, like `ipfs get -o out`.
+    ///
+    /// With `archive` set, `out` is the tar archive itself (gzipped with `compress`), otherwise
+    /// the content is unpacked there. Either way the archive is written out on its own thread
+    /// as it downloads.
+    pub async fn get<P: AsRef<Path>>(&self, req: &Get, out: P) -> Result<()> {
+        let tar = api::get(req.clone().archive(true));
+        let out = out.as_ref().to_path_buf();
+        if req.is_archive() {
+            return self.query_blocking(tar, move |body| {
+                io::copy(body, &mut File::create(&out)?).map(|_| ())
+            }).await;
+        }
+        let compressed = req.is_compressed();
+        self.query_blocking(tar, move |body| archive::extract(body, &out, compressed)).await
+    }
+
+    /// Yields the daemon's answer to `req` in chunks as it arrives.
+    pub async fn get_stream(&self, req: &Get) -> Result<ResponseStream> {
+        self.query_stream(api::get(req)).await
+    }
+
+    /// Copies the daemon's answer to `req` straight into `out`, returning the number of bytes written.
+    pub async fn get_to<W: AsyncWrite + Unpin>(&self, req: &Get, out: &mut W) -> Result<u64> {
+        self.query_to(api::get(req), out).await
+    }
+
+    pub async fn id(&self, req: &Id) -> Result<IdResponse> {
+        self.id_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn id_raw(&self, req: &Id) -> Result<Vec<u8>> {
+        self.query(api::id(req)).await
+    }
+
+    /// Returns the key named `name`, in the daemon's default format unless `format` is set.
+    pub async fn key_export(&self, name: &str, format: Option<&str>) -> Result<Vec<u8>> {
+        self.query(api::key_export(name, format)).await
+    }
+
+    pub async fn key_gen(&self, req: &KeyGen) -> Result<KeyResponse> {
+        self.key_gen_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn key_gen_raw(&self, req: &KeyGen) -> Result<Vec<u8>> {
+        self.query(api::key_gen(req)).await
+    }
+
+    pub async fn key_import<U: Into<Upload>>(&self, name: &str, key: U, format: Option<&str>) -> Result<KeyResponse> {
+        self.key_import_raw(name, key, format).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn key_import_raw<U: Into<Upload>>(&self, name: &str, key: U, format: Option<&str>) -> Result<Vec<u8>> {
+        self.query(api::key_import(name, key.into(), format)).await
+    }
+
+    pub async fn key_list(&self, req: &KeyList) -> Result<Vec<KeyResponse>> {
+        self.key_list_raw(req).await.and_then(|r| response::decode_list(&r, "Keys"))
+    }
+
+    pub async fn key_list_raw(&self, req: &KeyList) -> Result<Vec<u8>> {
+        self.query(api::key_list(req)).await
+    }
+
+    pub async fn key_rename(&self, req: &KeyRename) -> Result<KeyRenameResponse> {
+        self.key_rename_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn key_rename_raw(&self, req: &KeyRename) -> Result<Vec<u8>> {
+        self.query(api::key_rename(req)).await
+    }
+
+    pub async fn key_rm(&self, names: &[&str]) -> Result<Vec<KeyResponse>> {
+        self.key_rm_raw(names).await.and_then(|r| response::decode_list(&r, "Keys"))
+    }
+
+    pub async fn key_rm_raw(&self, names: &[&str]) -> Result<Vec<u8>> {
+        self.query(api::key_rm(names)).await
+    }
+
+    pub async fn log_level(&self, sli: &str, level: &str) -> Result<String> {
+        self.log_level_raw(sli, level).await.and_then(|r| response::decode_string(&r, "Message"))
+    }
+
+    pub async fn log_level_raw(&self, sli: &str, level: &str) -> Result<Vec<u8>> {
+        self.query(api::log_level(sli, level)).await
+    }
+
+    pub async fn log_ls(&self) -> Result<Vec<String>> {
+        self.log_ls_raw().await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn log_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::log_ls()).await
+    }
+
+    /// Follows the daemon's log, yielding entries as they are written until the stream is dropped.
+    pub async fn log_tail(&self) -> Result<JsonLinesStream<LogEvent>> {
+        self.query_lines(api::log_tail()).await
+    }
+
+    /// Like `log_tail`, but hands back the daemon's reply untouched.
+    pub async fn log_tail_stream(&self) -> Result<ResponseStream> {
+        self.query_stream(api::log_tail()).await
+    }
+
+    pub async fn ls(&self, req: &Ls) -> Result<Vec<LsObject>> {
+        self.ls_raw(req).await.and_then(|r| response::decode_list(&r, "Objects"))
+    }
+
+    pub async fn ls_raw(&self, req: &Ls) -> Result<Vec<u8>> {
+        self.query(api::ls(req)).await
+    }
+
+    pub async fn mount(&self, req: &Mount) -> Result<MountResponse> {
+        self.mount_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn mount_raw(&self, req: &Mount) -> Result<Vec<u8>> {
+        self.query(api::mount(req)).await
+    }
+
+    /// Decodes multibase text, whatever base it names.
+    pub async fn multibase_decode<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::multibase_decode(file.into())).await
+    }
+
+    pub async fn multibase_encode<U: Into<Upload>>(&self, file: U, base: &str) -> Result<String> {
+        self.multibase_encode_raw(file, base).await.and_then(|r| response::decode_text(&r))
+    }
+
+    pub async fn multibase_encode_raw<U: Into<Upload>>(&self, file: U, base: &str) -> Result<Vec<u8>> {
+        self.query(api::multibase_encode(file.into(), base)).await
+    }
+
+    pub async fn multibase_list(&self) -> Result<Vec<CodeAndName>> {
+        self.multibase_list_raw().await.and_then(|r| response::decode_array(&r))
+    }
+
+    pub async fn multibase_list_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::multibase_list()).await
+    }
+
+    pub async fn multibase_transcode<U: Into<Upload>>(&self, file: U, base: &str) -> Result<String> {
+        self.multibase_transcode_raw(file, base).await.and_then(|r| response::decode_text(&r))
+    }
+
+    pub async fn multibase_transcode_raw<U: Into<Upload>>(&self, file: U, base: &str) -> Result<Vec<u8>> {
+        self.query(api::multibase_transcode(file.into(), base)).await
+    }
+
+    pub async fn name_publish(&self, req: &NamePublish) -> Result<NamePublishResponse> {
+        self.name_publish_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn name_publish_raw(&self, req: &NamePublish) -> Result<Vec<u8>> {
+        self.query(api::name_publish(req)).await
+    }
+
+    pub async fn name_pubsub_cancel(&self, name: &str) -> Result<bool> {
+        self.name_pubsub_cancel_raw(name).await.and_then(|r| response::decode_bool(&r, "Canceled"))
+    }
+
+    pub async fn name_pubsub_cancel_raw(&self, name: &str) -> Result<Vec<u8>> {
+        self.query(api::name_pubsub_cancel(name)).await
+    }
+
+    pub async fn name_pubsub_state(&self) -> Result<bool> {
+        self.name_pubsub_state_raw().await.and_then(|r| response::decode_bool(&r, "Enabled"))
+    }
+
+    pub async fn name_pubsub_state_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::name_pubsub_state()).await
+    }
+
+    pub async fn name_pubsub_subs(&self) -> Result<Vec<String>> {
+        self.name_pubsub_subs_raw().await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn name_pubsub_subs_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::name_pubsub_subs()).await
+    }
+
+    pub async fn name_resolve(&self, req: &NameResolve) -> Result<String> {
+        self.name_resolve_raw(req).await.and_then(|r| response::decode_string(&r, "Path"))
+    }
+
+    pub async fn name_resolve_raw(&self, req: &NameResolve) -> Result<Vec<u8>> {
+        self.query(api::name_resolve(req)).await
+    }
+
+    pub async fn object_data(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::object_data(key)).await
+    }
+
+    pub async fn object_diff(&self, req: &ObjectDiff) -> Result<Vec<ObjectChange>> {
+        self.object_diff_raw(req).await.and_then(|r| response::decode_list(&r, "Changes"))
+    }
+
+    pub async fn object_diff_raw(&self, req: &ObjectDiff) -> Result<Vec<u8>> {
+        self.query(api::object_diff(req)).await
+    }
+
+    pub async fn object_get(&self, key: &str) -> Result<ObjectGetResponse> {
+        self.object_get_raw(key).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_get_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::object_get(key)).await
+    }
+
+    pub async fn object_links(&self, req: &ObjectLinks) -> Result<ObjectResponse> {
+        self.object_links_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_links_raw(&self, req: &ObjectLinks) -> Result<Vec<u8>> {
+        self.query(api::object_links(req)).await
+    }
+
+    pub async fn object_new(&self, obj: &str) -> Result<ObjectResponse> {
+        self.object_new_raw(obj).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_new_raw(&self, obj: &str) -> Result<Vec<u8>> {
+        self.query(api::object_new(obj)).await
+    }
+
+    pub async fn object_patch_add_link(&self, req: &ObjectPatchAddLink) -> Result<ObjectResponse> {
+        self.object_patch_add_link_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_patch_add_link_raw(&self, req: &ObjectPatchAddLink) -> Result<Vec<u8>> {
+        self.query(api::object_patch_add_link(req)).await
+    }
+
+    pub async fn object_patch_append_data<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<ObjectResponse> {
+        self.object_patch_append_data_raw(hash, file).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_patch_append_data_raw<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<Vec<u8>> {
+        self.query(api::object_patch_append_data(hash, file.into())).await
+    }
+
+    pub async fn object_patch_rm_link(&self, hash: &str, iname: &str) -> Result<ObjectResponse> {
+        self.object_patch_rm_link_raw(hash, iname).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_patch_rm_link_raw(&self, hash: &str, iname: &str) -> Result<Vec<u8>> {
+        self.query(api::object_patch_rm_link(hash, iname)).await
+    }
+
+    pub async fn object_patch_set_data<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<ObjectResponse> {
+        self.object_patch_set_data_raw(hash, file).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_patch_set_data_raw<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<Vec<u8>> {
+        self.query(api::object_patch_set_data(hash, file.into())).await
+    }
+
+    pub async fn object_put<U: Into<Upload>>(&self, file: U, req: &ObjectPut) -> Result<ObjectResponse> {
+        self.object_put_raw(file, req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_put_raw<U: Into<Upload>>(&self, file: U, req: &ObjectPut) -> Result<Vec<u8>> {
+        self.query(api::object_put(file.into(), req)).await
+    }
+
+    pub async fn object_stat(&self, key: &str) -> Result<ObjectStatResponse> {
+        self.object_stat_raw(key).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn object_stat_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::object_stat(key)).await
+    }
+
+    pub async fn pin_add(&self, req: &PinAdd) -> Result<PinResponse> {
+        self.pin_add_raw(req).await.and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub async fn pin_add_raw(&self, req: &PinAdd) -> Result<Vec<u8>> {
+        self.query(api::pin_add(req)).await
+    }
+
+    /// Like `pin_add`, but yields the number of nodes fetched so far as the pin goes,
+    /// ending with the pinned CIDs.
+    pub async fn pin_add_progress(&self, req: &PinAdd) -> Result<JsonLinesStream<PinResponse>> {
+        self.query_lines(api::pin_add_progress(req)).await
+    }
+
+    pub async fn pin_ls(&self, req: &PinLs) -> Result<Vec<Pin>> {
+        self.pin_ls_raw(req).await.and_then(|r| response::decode_pins(&r))
+    }
+
+    pub async fn pin_ls_raw(&self, req: &PinLs) -> Result<Vec<u8>> {
+        self.query(api::pin_ls(req)).await
+    }
+
+    pub async fn pin_remote_add(&self, req: &PinRemoteAdd) -> Result<RemotePin> {
+        self.pin_remote_add_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn pin_remote_add_raw(&self, req: &PinRemoteAdd) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_add(req)).await
+    }
+
+    pub async fn pin_remote_ls(&self, req: &PinRemoteLs) -> Result<Vec<RemotePin>> {
+        self.pin_remote_ls_raw(req).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn pin_remote_ls_raw(&self, req: &PinRemoteLs) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_ls(req)).await
+    }
+
+    pub async fn pin_remote_rm(&self, req: &PinRemoteRm) -> Result<()> {
+        self.pin_remote_rm_raw(req).await.map(|_| ())
+    }
+
+    pub async fn pin_remote_rm_raw(&self, req: &PinRemoteRm) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_rm(req)).await
+    }
+
+    pub async fn pin_remote_service_add(&self, service: &str, endpoint: &str, key: &str) -> Result<()> {
+        self.pin_remote_service_add_raw(service, endpoint, key).await.map(|_| ())
+    }
+
+    pub async fn pin_remote_service_add_raw(&self, service: &str, endpoint: &str, key: &str) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_service_add(service, endpoint, key)).await
+    }
+
+    pub async fn pin_remote_service_ls(&self) -> Result<Vec<PinRemoteService>> {
+        self.pin_remote_service_ls_raw().await.and_then(|r| response::decode_list(&r, "RemoteServices"))
+    }
+
+    pub async fn pin_remote_service_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_service_ls()).await
+    }
+
+    pub async fn pin_remote_service_rm(&self, service: &str) -> Result<()> {
+        self.pin_remote_service_rm_raw(service).await.map(|_| ())
+    }
+
+    pub async fn pin_remote_service_rm_raw(&self, service: &str) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_service_rm(service)).await
+    }
+
+    pub async fn pin_rm(&self, req: &PinRm) -> Result<PinResponse> {
+        self.pin_rm_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn pin_rm_raw(&self, req: &PinRm) -> Result<Vec<u8>> {
+        self.query(api::pin_rm(req)).await
+    }
+
+    pub async fn pin_update(&self, req: &PinUpdate) -> Result<PinResponse> {
+        self.pin_update_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn pin_update_raw(&self, req: &PinUpdate) -> Result<Vec<u8>> {
+        self.query(api::pin_update(req)).await
+    }
+
+    pub async fn pin_verify(&self, req: &PinVerify) -> Result<Vec<PinVerifyResponse>> {
+        self.pin_verify_raw(req).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn pin_verify_raw(&self, req: &PinVerify) -> Result<Vec<u8>> {
+        self.query(api::pin_verify(req)).await
+    }
+
+    /// Yields each line of the ping run as it happens. Feed them to a `PingSummary` for
+    /// latency statistics, or see `ping_summary`.
+    pub async fn ping(&self, req: &Ping) -> Result<JsonLinesStream<PingResponse>> {
+        self.query_lines(api::ping(req)).await
+    }
+
+    /// Runs the whole ping and sums up the results.
+    pub async fn ping_summary(&self, req: &Ping) -> Result<PingSummary> {
+        let mut pings = self.ping(req).await?;
+        let mut summary = PingSummary::new();
+        while let Some(ping) = pings.next().await {
+            summary.record(&ping?);
+        }
+        Ok(summary)
+    }
+
+    pub async fn ping_raw(&self, req: &Ping) -> Result<Vec<u8>> {
+        self.query(api::ping(req)).await
+    }
+
+    pub async fn pubsub_ls(&self) -> Result<Vec<String>> {
+        let encoding = self.pubsub_encoding().await;
+        self.pubsub_ls_raw().await.and_then(|r| response::decode_topics(&r, encoding))
+    }
+
+    pub async fn pubsub_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::pubsub_ls()).await
+    }
+
+    pub async fn pubsub_peers(&self, topic: &str) -> Result<Vec<String>> {
+        self.pubsub_peers_raw(topic).await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn pubsub_peers_raw(&self, topic: &str) -> Result<Vec<u8>> {
+        self.query(api::pubsub_peers(topic, self.topic_encoding().await)).await
+    }
+
+    /// Publishes `data` to `topic`. Binary data is fine: it goes in the request body.
+    pub async fn pubsub_pub<D: Into<Upload>>(&self, topic: &str, data: D) -> Result<()> {
+        self.pubsub_pub_raw(topic, data).await.map(|_| ())
+    }
+
+    pub async fn pubsub_pub_raw<D: Into<Upload>>(&self, topic: &str, data: D) -> Result<Vec<u8>> {
+        self.query(api::pubsub_pub(topic, data.into(), self.topic_encoding().await)).await
+    }
+
+    /// Subscribes to `topic`, yielding messages as they arrive until the stream is dropped.
+    pub async fn pubsub_sub(&self, topic: &str, discover: bool) -> Result<JsonLinesStream<PubsubMessage>> {
+        let encoding = self.pubsub_encoding().await;
+        let decode = response::pubsub_decoder(encoding);
+        let encoding = encoding.unwrap_or(PubsubEncoding::Multibase);
+        let response = self.send(api::pubsub_sub(topic, discover, encoding)).await?;
+        Ok(JsonLinesStream { lines: BufReader::new(response.into_body()).lines(), decode })
+    }
+
+    /// Like `pubsub_sub`, but hands back the daemon's reply untouched.
+    pub async fn pubsub_sub_stream(&self, topic: &str, discover: bool) -> Result<ResponseStream> {
+        self.query_stream(api::pubsub_sub(topic, discover, self.topic_encoding().await)).await
+    }
+
+    pub async fn refs_local(&self) -> Result<Vec<RefResponse>> {
+        self.refs_local_raw().await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn refs_local_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::refs_local()).await
+    }
+
+    pub async fn repo_fsck(&self) -> Result<String> {
+        self.repo_fsck_raw().await.and_then(|r| response::decode_string(&r, "Message"))
+    }
+
+    pub async fn repo_fsck_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::repo_fsck()).await
+    }
+
+    pub async fn repo_gc(&self, req: &RepoGc) -> Result<Vec<RepoGcResponse>> {
+        self.repo_gc_raw(req).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn repo_gc_raw(&self, req: &RepoGc) -> Result<Vec<u8>> {
+        self.query(api::repo_gc(req)).await
+    }
+
+    pub async fn repo_stat(&self, req: &RepoStat) -> Result<RepoStatResponse> {
+        self.repo_stat_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn repo_stat_raw(&self, req: &RepoStat) -> Result<Vec<u8>> {
+        self.query(api::repo_stat(req)).await
+    }
+
+    pub async fn repo_verify(&self) -> Result<Vec<RepoVerifyResponse>> {
+        self.repo_verify_raw().await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn repo_verify_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::repo_verify()).await
+    }
+
+    pub async fn repo_version(&self, req: &RepoVersion) -> Result<String> {
+        self.repo_version_raw(req).await.and_then(|r| response::decode_string(&r, "Version"))
+    }
+
+    pub async fn repo_version_raw(&self, req: &RepoVersion) -> Result<Vec<u8>> {
+        self.query(api::repo_version(req)).await
+    }
+
+    pub async fn resolve(&self, req: &Resolve) -> Result<String> {
+        self.resolve_raw(req).await.and_then(|r| response::decode_string(&r, "Path"))
+    }
+
+    pub async fn resolve_raw(&self, req: &Resolve) -> Result<Vec<u8>> {
+        self.query(api::resolve(req)).await
+    }
+
+    /// Like `dht_findpeer`, but asks every configured router rather than only the DHT.
+    pub async fn routing_findpeer(&self, peer: &str) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::routing_findpeer(peer)).await
+    }
+
+    pub async fn routing_findpeer_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::routing_findpeer(peer)).await
+    }
+
+    pub async fn routing_findprovs(&self, key: &str, num_providers: Option<u32>) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::routing_findprovs(key, num_providers)).await
+    }
+
+    pub async fn routing_findprovs_raw(&self, key: &str, num_providers: Option<u32>) -> Result<Vec<u8>> {
+        self.query(api::routing_findprovs(key, num_providers)).await
+    }
+
+    pub async fn routing_get(&self, key: &str) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::routing_get(key)).await
+    }
+
+    pub async fn routing_get_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::routing_get(key)).await
+    }
+
+    pub async fn routing_provide(&self, req: &DhtProvide) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::routing_provide(req)).await
+    }
+
+    pub async fn routing_provide_raw(&self, req: &DhtProvide) -> Result<Vec<u8>> {
+        self.query(api::routing_provide(req)).await
+    }
+
+    /// Stores `value` under `key`, which must be an IPNS or other signed record.
+    pub async fn routing_put<U: Into<Upload>>(&self, key: &str, value: U) -> Result<JsonLinesStream<DhtEvent>> {
+        self.query_lines(api::routing_put(key, value.into())).await
+    }
+
+    pub async fn routing_put_raw<U: Into<Upload>>(&self, key: &str, value: U) -> Result<Vec<u8>> {
+        self.query(api::routing_put(key, value.into())).await
+    }
+
+    /// Stops the daemon.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.shutdown_raw().await.map(|_| ())
+    }
+
+    pub async fn shutdown_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::shutdown()).await
+    }
+
+    pub async fn stats_bitswap(&self) -> Result<BitswapStatResponse> {
+        self.stats_bitswap_raw().await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn stats_bitswap_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::stats_bitswap()).await
+    }
+
+    pub async fn stats_bw(&self, req: &StatsBw) -> Result<Vec<BandwidthStats>> {
+        self.stats_bw_raw(req).await.and_then(|r| response::decode_lines(&r))
+    }
+
+    pub async fn stats_bw_raw(&self, req: &StatsBw) -> Result<Vec<u8>> {
+        self.query(api::stats_bw(req)).await
+    }
+
+    /// Samples bandwidth every `interval` until the stream is dropped.
+    pub async fn stats_bw_poll(&self, req: &StatsBw, interval: Duration) -> Result<JsonLinesStream<BandwidthStats>> {
+        self.query_lines(api::stats_bw_poll(req, interval)).await
+    }
+
+    pub async fn stats_repo(&self, req: &RepoStat) -> Result<RepoStatResponse> {
+        self.stats_repo_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn stats_repo_raw(&self, req: &RepoStat) -> Result<Vec<u8>> {
+        self.query(api::stats_repo(req)).await
+    }
+
+    pub async fn swarm_addrs_local(&self, peer: &str) -> Result<Vec<String>> {
+        self.swarm_addrs_local_raw(peer).await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn swarm_addrs_local_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_addrs_local(peer)).await
+    }
+
+    pub async fn swarm_connect(&self, addrs: &str) -> Result<Vec<String>> {
+        self.swarm_connect_raw(addrs).await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn swarm_connect_raw(&self, addrs: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_connect(addrs)).await
+    }
+
+    pub async fn swarm_disconnect(&self, addrs: &str) -> Result<Vec<String>> {
+        self.swarm_disconnect_raw(addrs).await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn swarm_disconnect_raw(&self, addrs: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_disconnect(addrs)).await
+    }
+
+    pub async fn swarm_filters_add(&self, maddr: &str) -> Result<Vec<String>> {
+        self.swarm_filters_add_raw(maddr).await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn swarm_filters_add_raw(&self, maddr: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_filters_add(maddr)).await
+    }
+
+    pub async fn swarm_filderst_rm(&self, maddr: &str) -> Result<Vec<String>> {
+        self.swarm_filderst_rm_raw(maddr).await.and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub async fn swarm_filderst_rm_raw(&self, maddr: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_filderst_rm(maddr)).await
+    }
+
+    pub async fn swarm_peering_add(&self, addr: &str) -> Result<PeeringResponse> {
+        self.swarm_peering_add_raw(addr).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn swarm_peering_add_raw(&self, addr: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_peering_add(addr)).await
+    }
+
+    pub async fn swarm_peering_ls(&self) -> Result<Vec<DhtPeer>> {
+        self.swarm_peering_ls_raw().await.and_then(|r| response::decode_list(&r, "Peers"))
+    }
+
+    pub async fn swarm_peering_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::swarm_peering_ls()).await
+    }
+
+    pub async fn swarm_peering_rm(&self, peer: &str) -> Result<PeeringResponse> {
+        self.swarm_peering_rm_raw(peer).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn swarm_peering_rm_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_peering_rm(peer)).await
+    }
+
+    pub async fn swarm_peers(&self) -> Result<Vec<SwarmPeer>> {
+        self.swarm_peers_raw().await.and_then(|r| response::decode_list(&r, "Peers"))
+    }
+
+    pub async fn swarm_peers_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::swarm_peers()).await
+    }
+
+    pub async fn tar_add<U: Into<Upload>>(&self, file: U) -> Result<AddResponse> {
+        self.tar_add_raw(file).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn tar_add_raw<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::tar_add(file.into())).await
+    }
+
+    pub async fn tar_cat(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::tar_cat(ipath)).await
+    }
+
+    pub async fn tour_list(&self) -> Result<Vec<u8>> {
+        self.query(api::tour_list()).await
+    }
+
+    pub async fn tour_next(&self) -> Result<Vec<u8>> {
+        self.query(api::tour_next()).await
+    }
+
+    pub async fn tour_restart(&self) -> Result<Vec<u8>> {
+        self.query(api::tour_restart()).await
+    }
+
+    pub async fn update(&self, sargs: &str) -> Result<Vec<u8>> {
+        self.query(api::update(sargs)).await
+    }
+
+    pub async fn version(&self, req: &Version) -> Result<VersionResponse> {
+        self.version_raw(req).await.and_then(|r| response::decode(&r))
+    }
+
+    pub async fn version_raw(&self, req: &Version) -> Result<Vec<u8>> {
+        self.query(api::version(req)).await
+    }
+
+
+    //------------------------------------------- QUERIES -----------------------------------------
+
+
+    async fn request_capabilities(&self) -> Result<DaemonCapabilities> {
+        let version = self.send_unchecked(api::version(&Version::new())).await?.bytes().await?;
+        let commands = self.send_unchecked(api::commands()).await?.bytes().await?;
+        Ok(DaemonCapabilities::new(response::decode(&version)?, &CommandTree::new(response::decode(&commands)?)))
+    }
+
+    /// Fetches the capabilities and remembers whether that worked.
+    async fn fetch_capabilities(&self) -> Result<Arc<DaemonCapabilities>> {
+        match self.request_capabilities().await {
+            Ok(caps) => Ok(self.capabilities.set(caps)),
+            Err(e) => {
+                self.capabilities.set_failed(&e);
+                Err(e)
+            }
+        }
+    }
+
+    /// The capabilities, fetched on first use, or `None` if they could not be.
+    async fn known_capabilities(&self) -> Option<Arc<DaemonCapabilities>> {
+        match self.capabilities.get() {
+            Some(Fetched::Known(caps)) => Some(caps),
+            Some(Fetched::Unknown { .. }) => None,
+            None => self.fetch_capabilities().await.ok(),
+        }
+    }
+
+    /// How the daemon encodes pubsub data, if its version is known.
+    async fn pubsub_encoding(&self) -> Option<PubsubEncoding> {
+        self.known_capabilities().await.map(|caps| caps.pubsub_encoding())
+    }
+
+    /// How to send topic names: as the daemon expects them, or as current daemons do.
+    async fn topic_encoding(&self) -> PubsubEncoding {
+        self.pubsub_encoding().await.unwrap_or(PubsubEncoding::Multibase)
+    }
+
+    /// Refuses calls the daemon does not serve. When its capabilities cannot be fetched the call
+    /// is sent anyway, and fails or not on its own.
+    async fn send(&self, req: Request) -> Result<Response<AsyncBody>> {
+        if let Some(caps) = self.known_capabilities().await {
+            caps.check(req.command())?;
+        }
+        self.send_unchecked(req).await
+    }
+
+    async fn send_unchecked(&self, req: Request) -> Result<Response<AsyncBody>> {
+        let link = req.link(&self.endpoint);
+        let (builder, body) = match req.body {
+            Some(form) => {
+                let content_type = form.content_type();
+                let body = upload_body(form.into_reader()?);
+                (isahc::Request::post(link).header("Content-Type", content_type), body)
+            }
+            // The daemon only answers POST, even for calls that upload nothing.
+            None => (isahc::Request::post(link), AsyncBody::empty()),
+        };
+        let request = self.dial(builder)?.body(body).map_err(isahc::Error::from)?;
+        let mut response = isahc::send_async(request).await?;
+        match response.status().as_u16() as u32 {
+            200..=299 => Ok(response),
+            code => Err(IpfsError::from_response(code, &response.bytes().await?)),
+        }
+    }
+
+    #[cfg(unix)]
+    fn dial(&self, builder: isahc::http::request::Builder) -> Result<isahc::http::request::Builder> {
+        use isahc::config::{Configurable, Dialer};
+        Ok(match self.endpoint.socket() {
+            Some(socket) => builder.dial(Dialer::unix_socket(socket)),
+            None => builder,
+        })
+    }
+
+    #[cfg(not(unix))]
+    fn dial(&self, builder: isahc::http::request::Builder) -> Result<isahc::http::request::Builder> {
+        match self.endpoint.socket() {
+            Some(_) => Err(IpfsError::InvalidEndpoint(self.endpoint.to_string())),
+            None => Ok(builder),
+        }
+    }
+
+    async fn query(&self, req: Request) -> Result<Vec<u8>> {
+        Ok(self.send(req).await?.bytes().await?)
+    }
+
+    async fn query_to<W: AsyncWrite + Unpin>(&self, req: Request, out: &mut W) -> Result<u64> {
+        let response = self.send(req).await?;
+        Ok(futures::io::copy(response.into_body(), out).await?)
+    }
+
+    /// Hands the response to `consume` on a thread of its own, so blocking file work stays off
+    /// the executor while the body downloads.
+    async fn query_blocking<F>(&self, req: Request, consume: F) -> Result<()>
+        where F: FnOnce(&mut dyn Read) -> io::Result<()> + Send + 'static
+    {
+        let mut body = self.send(req).await?.into_body();
+        let (mut tx, rx) = mpsc::channel::<io::Result<Vec<u8>>>(READ_AHEAD);
+        let (done_tx, done) = oneshot::channel();
+        thread::spawn(move || {
+            let mut chunks = BlockingRead(rx.into_async_read());
+            let _ = done_tx.send(consume(&mut chunks));
+        });
+        let mut buf = vec![0; CHUNK_SIZE];
+        loop {
+            let chunk = match body.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(n) => Ok(buf[..n].to_vec()),
+                Err(e) => Err(e),
+            };
+            let failed = chunk.is_err();
+            // A closed channel means `consume` is done early; its result says why.
+            if tx.send(chunk).await.is_err() || failed {
+                break;
+            }
+        }
+        drop(tx);
+        match done.await {
+            Ok(result) => result.map_err(IpfsError::from_io),
+            Err(_) => Err(IpfsError::Io(io::Error::new(io::ErrorKind::BrokenPipe, "writer thread panicked"))),
+        }
+    }
+
+    async fn query_stream(&self, req: Request) -> Result<ResponseStream> {
+        let response = self.send(req).await?;
+        Ok(ResponseStream { body: response.into_body(), buf: vec![0; CHUNK_SIZE] })
+    }
+
+    async fn query_lines<T: FromJson>(&self, req: Request) -> Result<JsonLinesStream<T>> {
+        let response = self.send(req).await?;
+        Ok(JsonLinesStream { lines: BufReader::new(response.into_body()).lines(), decode: response::decode::<T> })
+    }
+
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::answer;
+    use futures::executor::block_on;
+    use futures::stream::StreamExt;
+    use std::net::TcpListener;
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_multiaddrs_are_dialled() {
+        use std::os::unix::net::UnixListener;
+        let socket = std::env::temp_dir().join(format!("ipfs-api-async-{}.sock", std::process::id()));
+        let listener = UnixListener::bind(&socket).unwrap();
+        let ipfs = AsyncIpfs::connect(&format!("/unix{}", socket.display())).unwrap();
+        let server = thread::spawn(move || {
+            let bodies = ["", "", "hello"];
+            bodies.iter().zip(listener.incoming()).map(|(body, stream)| answer(stream.unwrap(), body)).collect::<Vec<_>>()
+        });
+        let content = block_on(ipfs.cat("QmHello"));
+        let requests = server.join().unwrap();
+        std::fs::remove_file(&socket).unwrap();
+        assert_eq!(b"hello", &content.unwrap()[..]);
+        assert!(requests[2].starts_with("POST /api/v0/cat?arg=QmHello "), "{:?}", requests);
+    }
+
+    #[test]
+    fn calls_without_a_body_are_posted() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let ipfs = AsyncIpfs::connect(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let server = thread::spawn(move || {
+            listener.incoming().take(3).map(|stream| answer(stream.unwrap(), "")).collect::<Vec<_>>()
+        });
+        block_on(ipfs.shutdown()).unwrap();
+        let requests = server.join().unwrap();
+        assert_eq!("POST /api/v0/shutdown HTTP/1.1", requests[2]);
+        assert!(requests.iter().all(|r| r.starts_with("POST ")), "{:?}", requests);
+    }
+
+    #[test]
+    fn cat_returns_correct_value() {
+        let mut ipfs = AsyncIpfs::new();
+        ipfs.host("http://localhost", 5001);
+        let ipfs_response = block_on(ipfs.cat("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH")).unwrap();
+        assert_eq!(b"hello, it really works!\n".to_vec(), ipfs_response);
+    }
+
+    #[test]
+    fn cat_stream_yields_value() {
+        let mut ipfs = AsyncIpfs::new();
+        ipfs.host("http://localhost", 5001);
+        let chunks = block_on(async {
+            ipfs.cat_stream("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH").await.unwrap()
+                .collect::<Vec<_>>().await
+        });
+        let content: Vec<u8> = chunks.into_iter().flat_map(|chunk| chunk.unwrap()).collect();
+        assert_eq!(b"hello, it really works!\n".to_vec(), content);
+    }
+
+    #[test]
+    fn add_missing_file_is_io_error() {
+        let mut ipfs = AsyncIpfs::new();
+        ipfs.host("http://localhost", 5001);
+        match block_on(ipfs.add("./does_not_exist.txt")) {
+            Err(IpfsError::Io(_)) => {}
+            other => panic!("expected an i/o error, got {:?}", other),
+        }
+    }
+}
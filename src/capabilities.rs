@@ -0,0 +1,142 @@
+use std::collections::BTreeSet;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::error::{IpfsError, Result};
+use crate::commands::CommandTree;
+use crate::response::{PubsubEncoding, VersionResponse};
+
+/// What the connected daemon is and which commands it serves, from its `version` and `commands`.
+///
+/// Clients fetch this once, before their first call, and refuse calls the daemon does not list
+/// with `IpfsError::Unsupported` instead of sending them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DaemonCapabilities {
+    pub version: VersionResponse,
+    commands: BTreeSet<String>,
+}
+
+impl DaemonCapabilities {
+    pub(crate) fn new(version: VersionResponse, tree: &CommandTree) -> DaemonCapabilities {
+        DaemonCapabilities { version, commands: tree.paths().into_iter().collect() }
+    }
+
+    /// Whether the daemon serves `command`, written as in the API path, e.g. `files/ls`.
+    pub fn supports(&self, command: &str) -> bool {
+        self.commands.contains(command.trim_matches('/'))
+    }
+
+    /// Every command the daemon serves, sorted.
+    pub fn commands(&self) -> impl Iterator<Item = &str> {
+        self.commands.iter().map(|c| c.as_str())
+    }
+
+    /// Whether the daemon is at least version `major.minor`. Versions that do not parse are
+    /// taken to be current.
+    fn at_least(&self, major: u64, minor: u64) -> bool {
+        let mut parts = self.version.version.trim_start_matches('v')
+            .split(|c: char| !c.is_ascii_digit())
+            .map(|part| part.parse::<u64>());
+        match (parts.next(), parts.next()) {
+            (Some(Ok(a)), Some(Ok(b))) => (a, b) >= (major, minor),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn pubsub_encoding(&self) -> PubsubEncoding {
+        if self.at_least(0, 11) { PubsubEncoding::Multibase } else { PubsubEncoding::Base64 }
+    }
+
+    pub(crate) fn check(&self, command: &str) -> Result<()> {
+        if self.supports(command) {
+            Ok(())
+        } else {
+            Err(IpfsError::Unsupported { command: command.to_string(), version: self.version.version.clone() })
+        }
+    }
+}
+
+/// How long calls go out unchecked after the daemon answered without usable capabilities.
+const RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// What a client and its clones have learned about the daemon's capabilities so far.
+#[derive(Clone, Default)]
+pub(crate) struct CapabilityCache {
+    fetched: Arc<Mutex<Option<Fetched>>>,
+}
+
+/// The outcome of fetching the capabilities.
+#[derive(Clone)]
+pub(crate) enum Fetched {
+    Known(Arc<DaemonCapabilities>),
+    /// Fetching them failed. Calls go out unchecked until `retry_at` rather than fetching again
+    /// every time.
+    Unknown { retry_at: Instant },
+}
+
+impl CapabilityCache {
+    /// What was fetched, or `None` if it has to be fetched (again).
+    pub fn get(&self) -> Option<Fetched> {
+        match *self.fetched.lock().unwrap_or_else(|e| e.into_inner()) {
+            Some(Fetched::Unknown { retry_at }) if Instant::now() >= retry_at => None,
+            ref fetched => fetched.clone(),
+        }
+    }
+
+    pub fn set(&self, caps: DaemonCapabilities) -> Arc<DaemonCapabilities> {
+        let caps = Arc::new(caps);
+        *self.fetched.lock().unwrap_or_else(|e| e.into_inner()) = Some(Fetched::Known(caps.clone()));
+        caps
+    }
+
+    /// Remembers that fetching failed with `e`. A daemon that could not be reached at all may
+    /// just not be up yet, so the next call asks again.
+    pub fn set_failed(&self, e: &IpfsError) {
+        let retry_at = if e.is_transport() { Instant::now() } else { Instant::now() + RETRY_AFTER };
+        *self.fetched.lock().unwrap_or_else(|e| e.into_inner()) = Some(Fetched::Unknown { retry_at });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::response::CommandsResponse;
+
+    fn command(name: &str, subcommands: Vec<CommandsResponse>) -> CommandsResponse {
+        CommandsResponse { name: name.to_string(), subcommands, options: Vec::new() }
+    }
+
+    fn version(version: &str) -> VersionResponse {
+        VersionResponse {
+            version: version.to_string(),
+            commit: String::new(),
+            repo: "13".to_string(),
+            system: String::new(),
+            golang: String::new(),
+        }
+    }
+
+    #[test]
+    fn nested_commands_are_listed_by_path() {
+        let root = command("ipfs", vec![
+            command("cat", vec![]),
+            command("files", vec![command("ls", vec![]), command("stat", vec![])]),
+        ]);
+        let caps = DaemonCapabilities::new(version("0.18.0"), &CommandTree::new(root));
+        assert_eq!(vec!["cat", "files", "files/ls", "files/stat"], caps.commands().collect::<Vec<_>>());
+        assert!(caps.supports("/files/ls"));
+        match caps.check("tar/add") {
+            Err(IpfsError::Unsupported { command, version }) => assert_eq!(("tar/add", "0.18.0"), (&command[..], &version[..])),
+            other => panic!("expected an unsupported error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pubsub_encoding_follows_the_version() {
+        let mut caps = DaemonCapabilities::new(version("0.11.0-rc1"), &CommandTree::new(command("ipfs", vec![])));
+        assert_eq!(PubsubEncoding::Multibase, caps.pubsub_encoding());
+        caps.version = version("0.4.10");
+        assert_eq!(PubsubEncoding::Base64, caps.pubsub_encoding());
+    }
+}
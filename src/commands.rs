@@ -0,0 +1,125 @@
+use crate::response::{CommandOption, CommandsResponse};
+
+/// Every command the daemon serves, with their options, as listed by `commands`.
+///
+/// Commands are looked up by their API path, e.g. `files/ls`:
+///
+/// ```no_run
+/// # use ipfs_api::IPFS;
+/// let tree = IPFS::new().commands().unwrap();
+/// for path in tree.paths() {
+///     let names: Vec<_> = tree.options(&path).unwrap().iter().map(|o| o.name()).collect();
+///     println!("{} {}", path, names.join(" "));
+/// }
+/// assert!(tree.accepts("files/ls", "long"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandTree {
+    root: CommandsResponse,
+}
+
+impl CommandTree {
+    pub fn new(root: CommandsResponse) -> CommandTree {
+        CommandTree { root }
+    }
+
+    /// The root command, whose options apply to every command.
+    pub fn root(&self) -> &CommandsResponse {
+        &self.root
+    }
+
+    /// The command at `path`, e.g. `files/ls`. Leading and trailing slashes are ignored.
+    pub fn get(&self, path: &str) -> Option<&CommandsResponse> {
+        path.trim_matches('/')
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(&self.root, |cmd, name| cmd.subcommand(name))
+    }
+
+    /// The options of the command at `path`, not counting the root's.
+    pub fn options(&self, path: &str) -> Option<&[CommandOption]> {
+        self.get(path).map(|cmd| &cmd.options[..])
+    }
+
+    /// Whether the command at `path` exists and takes `option`, by any of its names, either
+    /// itself or through the root.
+    pub fn accepts(&self, path: &str, option: &str) -> bool {
+        match self.get(path) {
+            Some(cmd) => cmd.option(option).is_some() || self.root.option(option).is_some(),
+            None => false,
+        }
+    }
+
+    /// The path of every command below the root, sorted.
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for sub in &self.root.subcommands {
+            collect(sub, "", &mut paths);
+        }
+        paths.sort();
+        paths
+    }
+}
+
+fn collect(cmd: &CommandsResponse, parent: &str, into: &mut Vec<String>) {
+    let path = if parent.is_empty() { cmd.name.clone() } else { format!("{}/{}", parent, cmd.name) };
+    for sub in &cmd.subcommands {
+        collect(sub, &path, into);
+    }
+    into.push(path);
+}
+
+impl CommandsResponse {
+    pub fn subcommand(&self, name: &str) -> Option<&CommandsResponse> {
+        self.subcommands.iter().find(|sub| sub.name == name)
+    }
+
+    /// The option called `name`, by its long name or any alias.
+    pub fn option(&self, name: &str) -> Option<&CommandOption> {
+        self.options.iter().find(|option| option.names.iter().any(|n| n == name))
+    }
+}
+
+impl CommandOption {
+    /// The option's long name, as sent in requests.
+    pub fn name(&self) -> &str {
+        self.names.first().map(|n| n.as_str()).unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn option(names: &[&str], option_type: Option<&str>) -> CommandOption {
+        CommandOption {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            option_type: option_type.map(|t| t.to_string()),
+        }
+    }
+
+    fn command(name: &str, options: Vec<CommandOption>, subcommands: Vec<CommandsResponse>) -> CommandsResponse {
+        CommandsResponse { name: name.to_string(), subcommands, options }
+    }
+
+    #[test]
+    fn commands_are_found_by_path() {
+        let tree = CommandTree::new(command("ipfs", vec![option(&["timeout"], Some("string"))], vec![
+            command("cat", vec![], vec![]),
+            command("files", vec![option(&["flush", "f"], Some("bool"))], vec![
+                command("ls", vec![option(&["long", "l"], None)], vec![]),
+            ]),
+        ]));
+        assert_eq!(vec!["cat", "files", "files/ls"], tree.paths());
+        assert_eq!("ls", tree.get("/files/ls/").unwrap().name);
+        assert_eq!("ipfs", tree.get("").unwrap().name);
+        assert!(tree.get("files/stat").is_none());
+        let flush = tree.get("files").unwrap().option("f").unwrap();
+        assert_eq!(("flush", Some("bool")), (flush.name(), flush.option_type.as_deref()));
+        assert!(tree.accepts("files/ls", "l"));
+        assert!(tree.accepts("files/ls", "timeout"));
+        assert!(!tree.accepts("files/ls", "flush"));
+        assert!(!tree.accepts("tar/add", "timeout"));
+    }
+}
@@ -0,0 +1,191 @@
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::{IpfsError, Result};
+
+/// Where the daemon's API is served, e.g. `http://127.0.0.1:5001` or the Unix socket
+/// `/unix/run/ipfs/api.sock`.
+///
+/// Build one from a multiaddr or a URL, or let `discover` find the local daemon the way the Go
+/// CLI does:
+///
+/// ```no_run
+/// # use ipfs_api::{Endpoint, IPFS};
+/// let local = IPFS::discover().unwrap();
+/// let remote = IPFS::connect("https://ipfs.example.com/rpc").unwrap();
+/// let mut ipfs = IPFS::new();
+/// ipfs.endpoint(Endpoint::parse("/ip4/10.0.0.2/tcp/5001").unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Endpoint {
+    base: String,
+    socket: Option<PathBuf>,
+}
+
+impl Default for Endpoint {
+    /// The daemon's own default, `/ip4/127.0.0.1/tcp/5001`.
+    fn default() -> Endpoint {
+        Endpoint::new("http://127.0.0.1:5001".to_string())
+    }
+}
+
+impl Endpoint {
+    pub(crate) fn new(base: String) -> Endpoint {
+        Endpoint { base: base.trim_end_matches('/').to_string(), socket: None }
+    }
+
+    /// A multiaddr if `addr` starts with `/`, a URL otherwise.
+    pub fn parse(addr: &str) -> Result<Endpoint> {
+        let addr = addr.trim();
+        if addr.starts_with('/') {
+            Endpoint::multiaddr(addr)
+        } else {
+            Endpoint::url(addr)
+        }
+    }
+
+    /// An `http` or `https` URL, which may carry a path prefix the API is served under, e.g.
+    /// `https://ipfs.example.com/rpc`.
+    pub fn url(url: &str) -> Result<Endpoint> {
+        let rest = url.strip_prefix("http://").or_else(|| url.strip_prefix("https://"));
+        match rest {
+            Some(rest) if !rest.is_empty() && !rest.starts_with('/') => Ok(Endpoint::new(url.to_string())),
+            _ => Err(IpfsError::InvalidEndpoint(url.to_string())),
+        }
+    }
+
+    /// A TCP multiaddr such as `/ip4/127.0.0.1/tcp/5001` or `/dns/node.example.com/tcp/443/https`,
+    /// or a Unix socket one such as `/unix/run/ipfs/api.sock`.
+    ///
+    /// Unspecified addresses, which daemons listening everywhere write to their `api` file, are
+    /// reached over loopback.
+    pub fn multiaddr(addr: &str) -> Result<Endpoint> {
+        let invalid = || IpfsError::InvalidEndpoint(addr.to_string());
+        if let Some(path) = addr.strip_prefix("/unix/") {
+            if path.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(Endpoint::unix_socket(format!("/{}", path)));
+        }
+        let parts: Vec<&str> = addr.trim_start_matches('/').split('/').collect();
+        let (proto, host, port, rest) = match parts.as_slice() {
+            [proto, host, "tcp", port, rest @ ..] => (*proto, *host, *port, rest),
+            _ => return Err(invalid()),
+        };
+        let host = match (proto, host) {
+            ("ip4", "0.0.0.0") => "127.0.0.1".to_string(),
+            ("ip6", "::") => "[::1]".to_string(),
+            ("ip6", host) => format!("[{}]", host),
+            ("ip4", host) | ("dns", host) | ("dns4", host) | ("dns6", host) => host.to_string(),
+            _ => return Err(invalid()),
+        };
+        let port: u16 = port.parse().map_err(|_| invalid())?;
+        let scheme = match rest {
+            [] | ["http"] => "http",
+            ["https"] | ["tls"] | ["tls", "http"] => "https",
+            _ => return Err(invalid()),
+        };
+        Ok(Endpoint::new(format!("{}://{}:{}", scheme, host, port)))
+    }
+
+    /// The daemon listening on the Unix domain socket at `path`.
+    pub fn unix_socket<P: Into<PathBuf>>(path: P) -> Endpoint {
+        // The host is only there to make a valid URL; the socket decides where calls go.
+        Endpoint { base: "http://localhost".to_string(), socket: Some(path.into()) }
+    }
+
+    /// The address in the `IPFS_API` environment variable, if it is set.
+    pub fn from_env() -> Result<Option<Endpoint>> {
+        match env::var("IPFS_API") {
+            Ok(addr) => Endpoint::parse(&addr).map(Some),
+            Err(env::VarError::NotPresent) => Ok(None),
+            Err(env::VarError::NotUnicode(addr)) => {
+                Err(IpfsError::InvalidEndpoint(addr.to_string_lossy().into_owned()))
+            }
+        }
+    }
+
+    /// The address a running daemon wrote to the `api` file of its repo at `repo`.
+    pub fn from_repo<P: AsRef<Path>>(repo: P) -> Result<Endpoint> {
+        let addr = fs::read_to_string(repo.as_ref().join("api"))?;
+        Endpoint::parse(&addr)
+    }
+
+    /// Finds the local daemon like the Go CLI: `IPFS_API` if set, then the `api` file in
+    /// `$IPFS_PATH` or `~/.ipfs`, then the default address.
+    pub fn discover() -> Result<Endpoint> {
+        if let Some(endpoint) = Endpoint::from_env()? {
+            return Ok(endpoint);
+        }
+        let repo = match env::var_os("IPFS_PATH") {
+            Some(path) => PathBuf::from(path),
+            None => match env::var_os("HOME") {
+                Some(home) => Path::new(&home).join(".ipfs"),
+                None => return Ok(Endpoint::default()),
+            },
+        };
+        match Endpoint::from_repo(repo) {
+            // No daemon is running on that repo right now.
+            Err(IpfsError::Io(ref e)) if e.kind() == io::ErrorKind::NotFound => Ok(Endpoint::default()),
+            found => found,
+        }
+    }
+
+    /// The URL every call's path is appended to.
+    pub fn base(&self) -> &str {
+        &self.base
+    }
+
+    /// The Unix domain socket calls are made over instead of TCP, if any.
+    pub fn socket(&self) -> Option<&Path> {
+        self.socket.as_deref()
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.socket {
+            Some(ref path) => write!(f, "/unix{}", path.display()),
+            None => f.write_str(&self.base),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn multiaddrs_become_urls() {
+        let cases = [
+            ("/ip4/127.0.0.1/tcp/5001", "http://127.0.0.1:5001"),
+            ("/ip4/0.0.0.0/tcp/5001", "http://127.0.0.1:5001"),
+            ("/ip6/::1/tcp/5001/http", "http://[::1]:5001"),
+            ("/dns/node.example.com/tcp/443/https", "https://node.example.com:443"),
+            ("https://node.example.com/rpc/", "https://node.example.com/rpc"),
+        ];
+        for &(addr, base) in cases.iter() {
+            assert_eq!(base, Endpoint::parse(addr).unwrap().base());
+        }
+        let socket = Endpoint::parse("/unix/run/ipfs/api.sock").unwrap();
+        assert_eq!(Some(Path::new("/run/ipfs/api.sock")), socket.socket());
+        assert_eq!("/unix/run/ipfs/api.sock", socket.to_string());
+        for addr in ["/unix/", "/ip4/127.0.0.1/udp/5001", "/ip4/127.0.0.1/tcp/http", "localhost:5001", "http://"].iter() {
+            assert!(Endpoint::parse(addr).is_err(), "{} should be refused", addr);
+        }
+    }
+
+    #[test]
+    fn repo_api_file_is_read() {
+        let repo = env::temp_dir().join(format!("ipfs-api-repo-{}", std::process::id()));
+        fs::create_dir_all(&repo).unwrap();
+        fs::write(repo.join("api"), "/ip4/127.0.0.1/tcp/5002\n").unwrap();
+        let endpoint = Endpoint::from_repo(&repo);
+        fs::remove_dir_all(&repo).unwrap();
+        assert_eq!("http://127.0.0.1:5002", endpoint.unwrap().base());
+    }
+}
@@ -0,0 +1,120 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::result;
+use std::str;
+
+/// Everything that can go wrong while talking to the daemon.
+#[derive(Debug)]
+pub enum IpfsError {
+    /// The request never completed: the daemon is down, the host is wrong, etc.
+    Transport(curl::Error),
+    /// The daemon answered with a non-2xx status and no recognizable error body.
+    Status(u32),
+    /// The daemon answered with its `{"Message": ..., "Code": ...}` error body.
+    Api { message: String, code: i64 },
+    /// A response body was not the JSON we expected.
+    Json(json::Error),
+    /// A local file or upload reader could not be read.
+    Io(io::Error),
+    /// An API address that is neither a supported multiaddr nor an `http(s)` URL.
+    InvalidEndpoint(String),
+    /// The connected daemon does not serve `command`, so the call was never sent.
+    Unsupported { command: String, version: String },
+    /// The request made by `AsyncIpfs` never completed.
+    #[cfg(feature = "async")]
+    AsyncTransport(isahc::Error),
+}
+
+pub type Result<T> = result::Result<T, IpfsError>;
+
+impl IpfsError {
+    /// Builds the error for a response with status `code` and body `body`.
+    pub(crate) fn from_response(code: u32, body: &[u8]) -> IpfsError {
+        let parsed = str::from_utf8(body).ok().and_then(|s| json::parse(s).ok());
+        if let Some(obj) = parsed {
+            if let Some(message) = obj["Message"].as_str() {
+                return IpfsError::Api {
+                    message: message.to_string(),
+                    code: obj["Code"].as_i64().unwrap_or(0),
+                };
+            }
+        }
+        IpfsError::Status(code)
+    }
+
+    /// Whether the daemon could not be reached at all, as opposed to answering badly.
+    pub(crate) fn is_transport(&self) -> bool {
+        match *self {
+            IpfsError::Transport(_) => true,
+            #[cfg(feature = "async")]
+            IpfsError::AsyncTransport(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Recovers the error a `ResponseReader` had to wrap into an `io::Error`.
+    pub(crate) fn from_io(e: io::Error) -> IpfsError {
+        match e.get_ref().map(|inner| inner.is::<IpfsError>()) {
+            // Checked just above, so neither unwrap can fail.
+            Some(true) => *e.into_inner().unwrap().downcast::<IpfsError>().unwrap(),
+            _ => IpfsError::Io(e),
+        }
+    }
+}
+
+impl fmt::Display for IpfsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IpfsError::Transport(ref e) => write!(f, "transport error: {}", e),
+            IpfsError::Status(code) => write!(f, "daemon returned HTTP {}", code),
+            IpfsError::Api { ref message, code } => write!(f, "daemon error {}: {}", code, message),
+            IpfsError::Json(ref e) => write!(f, "could not decode response: {}", e),
+            IpfsError::Io(ref e) => write!(f, "i/o error: {}", e),
+            IpfsError::InvalidEndpoint(ref addr) => write!(f, "invalid API address `{}`", addr),
+            IpfsError::Unsupported { ref command, ref version } => {
+                write!(f, "daemon {} does not support `{}`", version, command)
+            }
+            #[cfg(feature = "async")]
+            IpfsError::AsyncTransport(ref e) => write!(f, "transport error: {}", e),
+        }
+    }
+}
+
+impl error::Error for IpfsError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            IpfsError::Transport(ref e) => Some(e),
+            IpfsError::Json(ref e) => Some(e),
+            IpfsError::Io(ref e) => Some(e),
+            #[cfg(feature = "async")]
+            IpfsError::AsyncTransport(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<curl::Error> for IpfsError {
+    fn from(e: curl::Error) -> IpfsError {
+        IpfsError::Transport(e)
+    }
+}
+
+impl From<json::Error> for IpfsError {
+    fn from(e: json::Error) -> IpfsError {
+        IpfsError::Json(e)
+    }
+}
+
+impl From<io::Error> for IpfsError {
+    fn from(e: io::Error) -> IpfsError {
+        IpfsError::Io(e)
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<isahc::Error> for IpfsError {
+    fn from(e: isahc::Error) -> IpfsError {
+        IpfsError::AsyncTransport(e)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::query::Query;
+
+/// Options for `files_write`. Anything left unset is up to the daemon.
+///
+/// ```no_run
+/// # use ipfs_api::{FilesWriteOptions, IPFS};
+/// # let ipfs = IPFS::new();
+/// let log = std::fs::File::open("./big.log").unwrap();
+/// let upload = ipfs_api::Upload::reader("big.log", log);
+/// ipfs.files_write("/logs/big.log", upload, FilesWriteOptions::new().create(true).parents(true)).unwrap();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct FilesWriteOptions {
+    offset: Option<u64>,
+    count: Option<u64>,
+    create: Option<bool>,
+    truncate: Option<bool>,
+    parents: Option<bool>,
+    raw_leaves: Option<bool>,
+    cid_version: Option<u32>,
+    hash: Option<String>,
+}
+
+impl FilesWriteOptions {
+
+    pub fn new() -> FilesWriteOptions {
+        Default::default()
+    }
+
+    /// Where in the file to start writing. The daemon starts at the beginning.
+    pub fn offset(&mut self, inp: u64) -> &mut FilesWriteOptions {
+        self.offset = Some(inp);
+        self
+    }
+
+    /// Writes at most this many bytes of the upload. The daemon writes all of it.
+    pub fn count(&mut self, inp: u64) -> &mut FilesWriteOptions {
+        self.count = Some(inp);
+        self
+    }
+
+    /// Creates the file if it does not exist yet.
+    pub fn create(&mut self, inp: bool) -> &mut FilesWriteOptions {
+        self.create = Some(inp);
+        self
+    }
+
+    /// Empties the file before writing.
+    pub fn truncate(&mut self, inp: bool) -> &mut FilesWriteOptions {
+        self.truncate = Some(inp);
+        self
+    }
+
+    /// Creates missing parent directories along with the file.
+    pub fn parents(&mut self, inp: bool) -> &mut FilesWriteOptions {
+        self.parents = Some(inp);
+        self
+    }
+
+    /// Stores newly written data in raw leaves instead of wrapping it in UnixFS nodes.
+    pub fn raw_leaves(&mut self, inp: bool) -> &mut FilesWriteOptions {
+        self.raw_leaves = Some(inp);
+        self
+    }
+
+    /// The CID version of newly created nodes.
+    pub fn cid_version(&mut self, inp: u32) -> &mut FilesWriteOptions {
+        self.cid_version = Some(inp);
+        self
+    }
+
+    /// The multihash function of newly created nodes, e.g. `sha2-256` or `blake2b-256`.
+    pub fn hash(&mut self, inp: &str) -> &mut FilesWriteOptions {
+        self.hash = Some(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .opt("offset", self.offset)
+            .opt("count", self.count)
+            .opt("create", self.create)
+            .opt("truncate", self.truncate)
+            .opt("parents", self.parents)
+            .opt("raw-leaves", self.raw_leaves)
+            .opt("cid-version", self.cid_version)
+            .opt("hash", self.hash.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn options_use_the_daemons_names() {
+        let l = FilesWriteOptions::new().create(true).raw_leaves(true).cid_version(1).hash("blake2b-256").args();
+        assert_eq!("create=true&raw-leaves=true&cid-version=1&hash=blake2b-256", l.to_string());
+    }
+}
@@ -1,25 +1,69 @@
 extern crate curl;
+extern crate json;
 extern crate rustc_serialize;
 extern crate time;
-extern crate jsonify;
 
+mod add;
+mod api;
+mod archive;
+mod capabilities;
+mod commands;
+mod endpoint;
+mod error;
+mod files;
+mod mfs;
+mod multibase;
+mod multipart;
+mod params;
+mod query;
+mod response;
+mod transfer;
+
+#[cfg(feature = "async")]
+mod async_client;
+
+pub use add::AddOptions;
+pub use capabilities::DaemonCapabilities;
+pub use commands::CommandTree;
+pub use endpoint::Endpoint;
+pub use error::{IpfsError, Result};
+pub use files::FilesWriteOptions;
+pub use json::JsonValue;
+pub use mfs::{Mfs, MfsEntry, MfsFile, MfsFileType};
+pub use multipart::Upload;
+pub use params::{
+    BlockPut, BlockRm, CidFormat, DagImport, DagPut, DhtProvide, DhtPut, DhtQuery, DiagNet, Dns, FilesLs, FilesMkdir,
+    FilesRead, FilesRm, FilesStat, Get, Id, KeyGen, KeyList, KeyRename, Ls, Mount, NamePublish, NameResolve,
+    ObjectDiff, ObjectLinks, ObjectPatchAddLink, ObjectPut, PinAdd, PinLs, PinRemoteAdd, PinRemoteLs, PinRemoteRm,
+    PinRm, PinUpdate, PinVerify, Ping, RepoGc, RepoStat, RepoVersion, Resolve, StatsBw, Version,
+};
+pub use response::*;
+pub use transfer::{JsonLines, ResponseReader};
+
+#[cfg(feature = "async")]
+pub use async_client::{AsyncIpfs, JsonLinesStream, ResponseStream};
+
+use api::Request;
+use capabilities::{CapabilityCache, Fetched};
+use response::{last, FromJson, PubsubEncoding};
+use transfer::{Call, Pool};
+
+use std::fs::File;
+use std::io::Write;
 use std::path::Path;
-use curl::easy::{Easy, Form};
-
-fn parse_bool(inp: bool) -> String {
-    match inp {
-        true => "true".to_string(),
-        _ => "false".to_string(),
-    }
-}
-
-#[derive(Default)]
+use std::sync::Arc;
+use std::time::Duration;
+use curl::easy::List;
+
+/// A blocking client for the daemon's HTTP API.
+///
+/// Calls only borrow the client, so it can be shared between threads. Clones share their pool of
+/// keep-alive connections and what they learned about the daemon's capabilities.
+#[derive(Clone, Default)]
 pub struct IPFS {
-    host: String,
-    port: u16,
-    url: String,
-    args: String,
-    path: String,
+    endpoint: Endpoint,
+    pool: Pool,
+    capabilities: CapabilityCache,
 }
 
 impl IPFS {
@@ -29,590 +73,1311 @@ impl IPFS {
         ipfs
     }
 
-    pub fn host(&mut self, inp: &str, port: u16) -> &mut IPFS {
-        self.host = inp.to_string();
-        self.port = port;
-        self
+    /// A client for the daemon at `addr`, a multiaddr such as `/ip4/127.0.0.1/tcp/5001` or a URL.
+    pub fn connect(addr: &str) -> Result<IPFS> {
+        let mut ipfs = IPFS::new();
+        ipfs.endpoint(Endpoint::parse(addr)?);
+        Ok(ipfs)
     }
 
-    fn url(&mut self, inp: &str) -> &mut IPFS {
-        self.url = inp.to_string();
-        self
+    /// A client for the local daemon, found as by `Endpoint::discover`.
+    pub fn discover() -> Result<IPFS> {
+        let mut ipfs = IPFS::new();
+        ipfs.endpoint(Endpoint::discover()?);
+        Ok(ipfs)
     }
 
-    fn args(&mut self, inp: &str) -> &mut IPFS {
-        self.args = inp.to_string();
-        self
+    /// Shorthand for an `endpoint` at `inp`, e.g. `http://localhost`, and `port`.
+    pub fn host(&mut self, inp: &str, port: u16) -> &mut IPFS {
+        self.endpoint(Endpoint::new(format!("{}:{}", inp, port)))
     }
 
-    fn path(&mut self, p: &str) -> &mut IPFS {
-        self.path = p.to_string();
+    pub fn endpoint(&mut self, inp: Endpoint) -> &mut IPFS {
+        self.endpoint = inp;
+        // Another daemon may serve other commands.
+        self.capabilities = Default::default();
         self
     }
 
+    /// The daemon's version and commands, fetched on first use and cached from then on.
+    ///
+    /// If they could not be fetched, calls are sent unchecked; asking here fetches them again
+    /// and says why that fails.
+    pub fn capabilities(&self) -> Result<DaemonCapabilities> {
+        match self.capabilities.get() {
+            Some(Fetched::Known(caps)) => Ok((*caps).clone()),
+            _ => self.fetch_capabilities().map(|caps| (*caps).clone()),
+        }
+    }
 
     //--------------------------------------- API FUNCTIONS ---------------------------------------
 
 
-    pub fn add(&mut self, raw_path: &str) -> Vec<u8> {
-        self.url("/api/v0/add").path(raw_path).query_post_upload()
+    pub fn add<U: Into<Upload>>(&self, file: U) -> Result<AddResponse> {
+        self.add_raw(file).and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub fn add_raw<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::add(file.into()))
+    }
+
+    /// Like `add`, with the daemon options set in `options`.
+    pub fn add_with<U: Into<Upload>>(&self, file: U, options: &AddOptions) -> Result<AddResponse> {
+        self.add_with_raw(file, options).and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub fn add_with_raw<U: Into<Upload>>(&self, file: U, options: &AddOptions) -> Result<Vec<u8>> {
+        self.query(api::add_with(file.into(), options))
+    }
+
+    /// Like `add_with`, but reports how much of the upload the daemon has received as it goes,
+    /// ending with the added entry.
+    pub fn add_progress<U: Into<Upload>>(&self, file: U, options: &AddOptions) -> Result<JsonLines<AddEvent>> {
+        self.query_lines(api::add_progress(file.into(), options))
+    }
+
+    /// Adds the file or directory tree at `path`, one entry per file and directory.
+    pub fn add_path<P: AsRef<Path>>(&self, path: P, options: &AddOptions) -> Result<AddPathResponse> {
+        self.add_path_raw(path, options).and_then(|r| response::decode_add_path(&r))
+    }
+
+    pub fn add_path_raw<P: AsRef<Path>>(&self, path: P, options: &AddOptions) -> Result<Vec<u8>> {
+        self.query(api::add_path(path.as_ref(), options)?)
+    }
+
+    /// Like `add_path`, but reports the daemon's progress through every file as it goes,
+    /// followed by the added entries.
+    pub fn add_path_progress<P: AsRef<Path>>(&self, path: P, options: &AddOptions) -> Result<JsonLines<AddEvent>> {
+        self.query_lines(api::add_path_progress(path.as_ref(), options)?)
+    }
+
+    pub fn bitswap_ledger(&self, peer: &str) -> Result<BitswapLedgerResponse> {
+        self.bitswap_ledger_raw(peer).and_then(|r| response::decode(&r))
+    }
+
+    pub fn bitswap_ledger_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::bitswap_ledger(peer))
+    }
+
+    pub fn bitswap_stat(&self) -> Result<BitswapStatResponse> {
+        self.bitswap_stat_raw().and_then(|r| response::decode(&r))
+    }
+
+    pub fn bitswap_stat_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bitswap_stat())
+    }
+
+    pub fn bitswap_unwant(&self, key: &str) -> Result<()> {
+        self.bitswap_unwant_raw(key).map(|_| ())
+    }
+
+    pub fn bitswap_unwant_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::bitswap_unwant(key))
+    }
+
+    pub fn bitswap_wantlist(&self, peer: &str) -> Result<Vec<String>> {
+        self.bitswap_wantlist_raw(peer).and_then(|r| response::decode_cids(&r, "Keys"))
+    }
+
+    pub fn bitswap_wantlist_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::bitswap_wantlist(peer))
+    }
+
+    pub fn block_get(&self, hash: &str) -> Result<Vec<u8>> {
+        self.query(api::block_get(hash))
+    }
+
+    /// Like `block_get`, but hands the block back as it arrives instead of buffering it.
+    pub fn block_get_reader(&self, hash: &str) -> Result<ResponseReader> {
+        self.query_reader(api::block_get(hash))
+    }
+
+    /// Like `block_get`, but copies the block straight into `out`, returning the number of bytes written.
+    pub fn block_get_to<W: Write + ?Sized>(&self, hash: &str, out: &mut W) -> Result<u64> {
+        self.query_to(api::block_get(hash), out)
+    }
+
+    pub fn block_put<U: Into<Upload>>(&self, file: U, req: &BlockPut) -> Result<BlockStatResponse> {
+        self.block_put_raw(file, req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn block_put_raw<U: Into<Upload>>(&self, file: U, req: &BlockPut) -> Result<Vec<u8>> {
+        self.query(api::block_put(file.into(), req))
+    }
+
+    pub fn block_rm(&self, req: &BlockRm) -> Result<Vec<BlockRmResponse>> {
+        self.block_rm_raw(req).and_then(|r| response::decode_lines(&r))
+    }
+
+    pub fn block_rm_raw(&self, req: &BlockRm) -> Result<Vec<u8>> {
+        self.query(api::block_rm(req))
+    }
+
+    pub fn block_stat(&self, hash: &str) -> Result<BlockStatResponse> {
+        self.block_stat_raw(hash).and_then(|r| response::decode(&r))
+    }
+
+    pub fn block_stat_raw(&self, hash: &str) -> Result<Vec<u8>> {
+        self.query(api::block_stat(hash))
+    }
+
+    pub fn bootstrap_add_default(&self) -> Result<Vec<String>> {
+        self.bootstrap_add_default_raw().and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub fn bootstrap_add_default_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_add_default())
+    }
+
+    pub fn bootstrap_add(&self, peers: &[&str]) -> Result<Vec<String>> {
+        self.bootstrap_add_raw(peers).and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub fn bootstrap_add_raw(&self, peers: &[&str]) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_add(peers))
+    }
+
+    pub fn bootstrap_list(&self) -> Result<Vec<String>> {
+        self.bootstrap_list_raw().and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub fn bootstrap_list_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_list())
+    }
+
+    pub fn bootstrap_rm_all(&self) -> Result<Vec<String>> {
+        self.bootstrap_rm_all_raw().and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub fn bootstrap_rm_all_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_rm_all())
+    }
+
+    pub fn bootstrap_rm(&self, peers: &[&str]) -> Result<Vec<String>> {
+        self.bootstrap_rm_raw(peers).and_then(|r| response::decode_strings(&r, "Peers"))
+    }
+
+    pub fn bootstrap_rm_raw(&self, peers: &[&str]) -> Result<Vec<u8>> {
+        self.query(api::bootstrap_rm(peers))
+    }
+
+    pub fn cat(&self, hash: &str) -> Result<Vec<u8>> {
+        self.query(api::cat(hash))
+    }
+
+    /// Like `cat`, but hands the content back as it arrives instead of buffering it.
+    pub fn cat_reader(&self, hash: &str) -> Result<ResponseReader> {
+        self.query_reader(api::cat(hash))
+    }
+
+    /// Like `cat`, but copies the content straight into `out`, returning the number of bytes written.
+    pub fn cat_to<W: Write + ?Sized>(&self, hash: &str, out: &mut W) -> Result<u64> {
+        self.query_to(api::cat(hash), out)
+    }
+
+    pub fn cid_base32(&self, cid: &str) -> Result<CidFormatResponse> {
+        self.cid_base32_raw(cid).and_then(|r| response::decode(&r))
+    }
+
+    pub fn cid_base32_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::cid_base32(cid))
+    }
+
+    pub fn cid_bases(&self) -> Result<Vec<CodeAndName>> {
+        self.cid_bases_raw().and_then(|r| response::decode_array(&r))
+    }
+
+    pub fn cid_bases_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::cid_bases())
+    }
+
+    pub fn cid_codecs(&self) -> Result<Vec<CodeAndName>> {
+        self.cid_codecs_raw().and_then(|r| response::decode_array(&r))
+    }
+
+    pub fn cid_codecs_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::cid_codecs())
+    }
+
+    pub fn cid_format(&self, req: &CidFormat) -> Result<CidFormatResponse> {
+        self.cid_format_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn cid_format_raw(&self, req: &CidFormat) -> Result<Vec<u8>> {
+        self.query(api::cid_format(req))
+    }
+
+    pub fn cid_hashes(&self) -> Result<Vec<CodeAndName>> {
+        self.cid_hashes_raw().and_then(|r| response::decode_array(&r))
+    }
+
+    pub fn cid_hashes_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::cid_hashes())
+    }
+
+    pub fn commands(&self) -> Result<CommandTree> {
+        self.commands_raw().and_then(|r| response::decode(&r).map(CommandTree::new))
+    }
+
+    pub fn commands_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::commands())
+    }
+
+    pub fn config_replace<U: Into<Upload>>(&self, file: U) -> Result<()> {
+        self.config_replace_raw(file).map(|_| ())
+    }
+
+    pub fn config_replace_raw<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::config_replace(file.into()))
+    }
+
+    pub fn config_show(&self) -> Result<JsonValue> {
+        self.config_show_raw().and_then(|r| response::parse(&r))
+    }
+
+    pub fn config_show_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::config_show())
+    }
+
+    /// Returns the CAR file of the DAG below `cid`.
+    pub fn dag_export(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_export(cid))
+    }
+
+    /// Like `dag_export`, but hands the content back as it arrives instead of buffering it.
+    pub fn dag_export_reader(&self, cid: &str) -> Result<ResponseReader> {
+        self.query_reader(api::dag_export(cid))
+    }
+
+    /// Like `dag_export`, but copies the content straight into `out`, returning the number of bytes written.
+    pub fn dag_export_to<W: Write + ?Sized>(&self, cid: &str, out: &mut W) -> Result<u64> {
+        self.query_to(api::dag_export(cid), out)
+    }
+
+    pub fn dag_get(&self, inp: &str) -> Result<JsonValue> {
+        self.dag_get_raw(inp).and_then(|r| response::parse(&r))
+    }
+
+    pub fn dag_get_raw(&self, inp: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_get(inp))
+    }
+
+    /// Imports the blocks of a CAR file, returning its roots.
+    pub fn dag_import<U: Into<Upload>>(&self, file: U, req: &DagImport) -> Result<Vec<DagImportRoot>> {
+        self.dag_import_raw(file, req).and_then(|r| response::decode_dag_import(&r))
+    }
+
+    pub fn dag_import_raw<U: Into<Upload>>(&self, file: U, req: &DagImport) -> Result<Vec<u8>> {
+        self.query(api::dag_import(file.into(), req))
+    }
+
+    pub fn dag_put<U: Into<Upload>>(&self, file: U, req: &DagPut) -> Result<String> {
+        self.dag_put_raw(file, req).and_then(|r| response::decode_cid(&r, "Cid"))
+    }
+
+    pub fn dag_put_raw<U: Into<Upload>>(&self, file: U, req: &DagPut) -> Result<Vec<u8>> {
+        self.query(api::dag_put(file.into(), req))
+    }
+
+    pub fn dag_resolve(&self, ipath: &str) -> Result<DagResolveResponse> {
+        self.dag_resolve_raw(ipath).and_then(|r| response::decode(&r))
+    }
+
+    pub fn dag_resolve_raw(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_resolve(ipath))
+    }
+
+    /// The size and block count of the DAG under `cid`. The daemon reports its progress along
+    /// the way; this returns the final count.
+    pub fn dag_stat(&self, cid: &str) -> Result<DagStatResponse> {
+        self.dag_stat_raw(cid).and_then(|r| response::decode_lines(&r).and_then(last))
+    }
+
+    pub fn dag_stat_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::dag_stat(cid))
+    }
+
+    /// Yields the query's events as the daemon reports them; dropping it stops the query.
+    pub fn dht_findpeer(&self, req: &DhtQuery) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::dht_findpeer(req))
+    }
+
+    pub fn dht_findpeer_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_findpeer(req))
+    }
+
+    pub fn dht_findprovs(&self, req: &DhtQuery) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::dht_findprovs(req))
+    }
+
+    pub fn dht_findprovs_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_findprovs(req))
+    }
+
+    pub fn dht_get(&self, req: &DhtQuery) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::dht_get(req))
+    }
+
+    pub fn dht_get_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_get(req))
+    }
+
+    pub fn dht_provide(&self, req: &DhtProvide) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::dht_provide(req))
+    }
+
+    pub fn dht_provide_raw(&self, req: &DhtProvide) -> Result<Vec<u8>> {
+        self.query(api::dht_provide(req))
+    }
+
+    pub fn dht_put(&self, req: &DhtPut) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::dht_put(req))
+    }
+
+    pub fn dht_put_raw(&self, req: &DhtPut) -> Result<Vec<u8>> {
+        self.query(api::dht_put(req))
+    }
+
+    pub fn dht_query(&self, req: &DhtQuery) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::dht_query(req))
+    }
+
+    pub fn dht_query_raw(&self, req: &DhtQuery) -> Result<Vec<u8>> {
+        self.query(api::dht_query(req))
+    }
+
+    pub fn diag_cmds_clear(&self) -> Result<()> {
+        self.diag_cmds_clear_raw().map(|_| ())
+    }
+
+    pub fn diag_cmds_clear_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::diag_cmds_clear())
+    }
+
+    /// Returns a zip of the daemon's profiles, collected over `profile_time`.
+    pub fn diag_profile(&self, profile_time: Duration) -> Result<Vec<u8>> {
+        self.query(api::diag_profile(profile_time))
+    }
+
+    /// Like `diag_profile`, but hands the content back as it arrives instead of buffering it.
+    pub fn diag_profile_reader(&self, profile_time: Duration) -> Result<ResponseReader> {
+        self.query_reader(api::diag_profile(profile_time))
+    }
+
+    /// Like `diag_profile`, but copies the content straight into `out`, returning the number of bytes written.
+    pub fn diag_profile_to<W: Write + ?Sized>(&self, profile_time: Duration, out: &mut W) -> Result<u64> {
+        self.query_to(api::diag_profile(profile_time), out)
+    }
+
+    pub fn diag_set_time(&self, ntime: &str) -> Result<()> {
+        self.diag_set_time_raw(ntime).map(|_| ())
+    }
+
+    pub fn diag_set_time_raw(&self, ntime: &str) -> Result<Vec<u8>> {
+        self.query(api::diag_set_time(ntime))
+    }
+
+    pub fn diag_net(&self, req: &DiagNet) -> Result<Vec<u8>> {
+        self.query(api::diag_net(req))
+    }
+
+    pub fn diag_sys(&self) -> Result<JsonValue> {
+        self.diag_sys_raw().and_then(|r| response::parse(&r))
+    }
+
+    pub fn diag_sys_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::diag_sys())
+    }
+
+    pub fn dns(&self, req: &Dns) -> Result<String> {
+        self.dns_raw(req).and_then(|r| response::decode_string(&r, "Path"))
+    }
+
+    pub fn dns_raw(&self, req: &Dns) -> Result<Vec<u8>> {
+        self.query(api::dns(req))
+    }
+
+    pub fn file_ls(&self, ipath: &str) -> Result<FileLsResponse> {
+        self.file_ls_raw(ipath).and_then(|r| response::decode(&r))
+    }
+
+    pub fn file_ls_raw(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::file_ls(ipath))
+    }
+
+    pub fn files_chcid(&self, ipath: &str, cid_version: Option<u32>, hash: Option<&str>) -> Result<()> {
+        self.files_chcid_raw(ipath, cid_version, hash).map(|_| ())
+    }
+
+    pub fn files_chcid_raw(&self, ipath: &str, cid_version: Option<u32>, hash: Option<&str>) -> Result<Vec<u8>> {
+        self.query(api::files_chcid(ipath, cid_version, hash))
+    }
+
+    pub fn files_cp(&self, from: &str, to: &str) -> Result<()> {
+        self.files_cp_raw(from, to).map(|_| ())
+    }
+
+    pub fn files_cp_raw(&self, from: &str, to: &str) -> Result<Vec<u8>> {
+        self.query(api::files_cp(from, to))
+    }
+
+    pub fn files_flush(&self, ipath: &str) -> Result<()> {
+        self.files_flush_raw(ipath).map(|_| ())
+    }
+
+    pub fn files_flush_raw(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::files_flush(ipath))
+    }
+
+    pub fn files_ls(&self, req: &FilesLs) -> Result<Vec<FilesEntry>> {
+        self.files_ls_raw(req).and_then(|r| response::decode_list(&r, "Entries"))
+    }
+
+    pub fn files_ls_raw(&self, req: &FilesLs) -> Result<Vec<u8>> {
+        self.query(api::files_ls(req))
+    }
+
+    pub fn files_mkdir(&self, req: &FilesMkdir) -> Result<()> {
+        self.files_mkdir_raw(req).map(|_| ())
+    }
+
+    pub fn files_mkdir_raw(&self, req: &FilesMkdir) -> Result<Vec<u8>> {
+        self.query(api::files_mkdir(req))
+    }
+
+    pub fn files_mv(&self, source: &str, dest: &str) -> Result<()> {
+        self.files_mv_raw(source, dest).map(|_| ())
+    }
+
+    pub fn files_mv_raw(&self, source: &str, dest: &str) -> Result<Vec<u8>> {
+        self.query(api::files_mv(source, dest))
+    }
+
+    pub fn files_read(&self, req: &FilesRead) -> Result<Vec<u8>> {
+        self.query(api::files_read(req))
+    }
+
+    /// Like `files_read`, but hands the file content back as it arrives instead of buffering it.
+    pub fn files_read_reader(&self, req: &FilesRead) -> Result<ResponseReader> {
+        self.query_reader(api::files_read(req))
+    }
+
+    /// Like `files_read`, but copies the file content straight into `out`, returning the number of bytes written.
+    pub fn files_read_to<W: Write + ?Sized>(&self, req: &FilesRead, out: &mut W) -> Result<u64> {
+        self.query_to(api::files_read(req), out)
+    }
+
+    pub fn files_rm(&self, req: &FilesRm) -> Result<()> {
+        self.files_rm_raw(req).map(|_| ())
+    }
+
+    pub fn files_rm_raw(&self, req: &FilesRm) -> Result<Vec<u8>> {
+        self.query(api::files_rm(req))
+    }
+
+    pub fn files_stat(&self, req: &FilesStat) -> Result<FilesStatResponse> {
+        self.files_stat_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn files_stat_raw(&self, req: &FilesStat) -> Result<Vec<u8>> {
+        self.query(api::files_stat(req))
+    }
+
+    /// Writes `data` into the MFS file at `ipath`. Readers are streamed, so large writes are not
+    /// buffered in memory.
+    pub fn files_write<U: Into<Upload>>(&self, ipath: &str, data: U, options: &FilesWriteOptions) -> Result<()> {
+        self.files_write_raw(ipath, data, options).map(|_| ())
+    }
+
+    pub fn files_write_raw<U: Into<Upload>>(&self, ipath: &str, data: U, options: &FilesWriteOptions) -> Result<Vec<u8>> {
+        self.query(api::files_write(ipath, data.into(), options))
+    }
+
+    pub fn filestore_dups(&self) -> Result<Vec<RefResponse>> {
+        self.filestore_dups_raw().and_then(|r| response::decode_lines(&r))
+    }
+
+    pub fn filestore_dups_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::filestore_dups())
+    }
+
+    pub fn filestore_ls(&self, cid: &str) -> Result<Vec<FilestoreObject>> {
+        self.filestore_ls_raw(cid).and_then(|r| response::decode_lines(&r))
+    }
+
+    pub fn filestore_ls_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::filestore_ls(cid))
+    }
+
+    pub fn filestore_verify(&self, cid: &str) -> Result<Vec<FilestoreObject>> {
+        self.filestore_verify_raw(cid).and_then(|r| response::decode_lines(&r))
+    }
+
+    pub fn filestore_verify_raw(&self, cid: &str) -> Result<Vec<u8>> {
+        self.query(api::filestore_verify(cid))
+    }
+
+    /// Fetches the requested path into the local path `out`, like `ipfs get -o out`.
+    ///
+    /// With `archive` set, `out` is the tar archive itself (gzipped with `compress`), otherwise
+    /// the content is unpacked there as it arrives.
+    pub fn get<P: AsRef<Path>>(&self, req: &Get, out: P) -> Result<()> {
+        let tar = api::get(req.clone().archive(true));
+        if req.is_archive() {
+            let mut file = File::create(out)?;
+            return self.query_to(tar, &mut file).map(|_| ());
+        }
+        let reader = self.query_reader(tar)?;
+        archive::extract(reader, out.as_ref(), req.is_compressed()).map_err(IpfsError::from_io)
+    }
+
+    /// Hands the daemon's answer to `req` back as it arrives.
+    pub fn get_reader(&self, req: &Get) -> Result<ResponseReader> {
+        self.query_reader(api::get(req))
+    }
+
+    /// Copies the daemon's answer to `req` straight into `out`, returning the number of bytes written.
+    pub fn get_to<W: Write + ?Sized>(&self, req: &Get, out: &mut W) -> Result<u64> {
+        self.query_to(api::get(req), out)
+    }
+
+    pub fn id(&self, req: &Id) -> Result<IdResponse> {
+        self.id_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn id_raw(&self, req: &Id) -> Result<Vec<u8>> {
+        self.query(api::id(req))
+    }
+
+    /// Returns the key named `name`, in the daemon's default format unless `format` is set.
+    pub fn key_export(&self, name: &str, format: Option<&str>) -> Result<Vec<u8>> {
+        self.query(api::key_export(name, format))
+    }
+
+    pub fn key_gen(&self, req: &KeyGen) -> Result<KeyResponse> {
+        self.key_gen_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn key_gen_raw(&self, req: &KeyGen) -> Result<Vec<u8>> {
+        self.query(api::key_gen(req))
+    }
+
+    pub fn key_import<U: Into<Upload>>(&self, name: &str, key: U, format: Option<&str>) -> Result<KeyResponse> {
+        self.key_import_raw(name, key, format).and_then(|r| response::decode(&r))
+    }
+
+    pub fn key_import_raw<U: Into<Upload>>(&self, name: &str, key: U, format: Option<&str>) -> Result<Vec<u8>> {
+        self.query(api::key_import(name, key.into(), format))
+    }
+
+    pub fn key_list(&self, req: &KeyList) -> Result<Vec<KeyResponse>> {
+        self.key_list_raw(req).and_then(|r| response::decode_list(&r, "Keys"))
+    }
+
+    pub fn key_list_raw(&self, req: &KeyList) -> Result<Vec<u8>> {
+        self.query(api::key_list(req))
+    }
+
+    pub fn key_rename(&self, req: &KeyRename) -> Result<KeyRenameResponse> {
+        self.key_rename_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn key_rename_raw(&self, req: &KeyRename) -> Result<Vec<u8>> {
+        self.query(api::key_rename(req))
+    }
+
+    pub fn key_rm(&self, names: &[&str]) -> Result<Vec<KeyResponse>> {
+        self.key_rm_raw(names).and_then(|r| response::decode_list(&r, "Keys"))
+    }
+
+    pub fn key_rm_raw(&self, names: &[&str]) -> Result<Vec<u8>> {
+        self.query(api::key_rm(names))
+    }
+
+    pub fn log_level(&self, sli: &str, level: &str) -> Result<String> {
+        self.log_level_raw(sli, level).and_then(|r| response::decode_string(&r, "Message"))
+    }
+
+    pub fn log_level_raw(&self, sli: &str, level: &str) -> Result<Vec<u8>> {
+        self.query(api::log_level(sli, level))
+    }
+
+    pub fn log_ls(&self) -> Result<Vec<String>> {
+        self.log_ls_raw().and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub fn log_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::log_ls())
+    }
+
+    /// Follows the daemon's log, yielding entries as they are written until the iterator is dropped.
+    pub fn log_tail(&self) -> Result<JsonLines<LogEvent>> {
+        self.query_lines(api::log_tail())
+    }
+
+    /// Like `log_tail`, but hands back the daemon's reply untouched.
+    pub fn log_tail_reader(&self) -> Result<ResponseReader> {
+        self.query_reader(api::log_tail())
+    }
+
+    pub fn ls(&self, req: &Ls) -> Result<Vec<LsObject>> {
+        self.ls_raw(req).and_then(|r| response::decode_list(&r, "Objects"))
+    }
+
+    pub fn ls_raw(&self, req: &Ls) -> Result<Vec<u8>> {
+        self.query(api::ls(req))
+    }
+
+    pub fn mount(&self, req: &Mount) -> Result<MountResponse> {
+        self.mount_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn mount_raw(&self, req: &Mount) -> Result<Vec<u8>> {
+        self.query(api::mount(req))
+    }
+
+    /// Decodes multibase text, whatever base it names.
+    pub fn multibase_decode<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::multibase_decode(file.into()))
+    }
+
+    pub fn multibase_encode<U: Into<Upload>>(&self, file: U, base: &str) -> Result<String> {
+        self.multibase_encode_raw(file, base).and_then(|r| response::decode_text(&r))
+    }
+
+    pub fn multibase_encode_raw<U: Into<Upload>>(&self, file: U, base: &str) -> Result<Vec<u8>> {
+        self.query(api::multibase_encode(file.into(), base))
+    }
+
+    pub fn multibase_list(&self) -> Result<Vec<CodeAndName>> {
+        self.multibase_list_raw().and_then(|r| response::decode_array(&r))
+    }
+
+    pub fn multibase_list_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::multibase_list())
+    }
+
+    pub fn multibase_transcode<U: Into<Upload>>(&self, file: U, base: &str) -> Result<String> {
+        self.multibase_transcode_raw(file, base).and_then(|r| response::decode_text(&r))
+    }
+
+    pub fn multibase_transcode_raw<U: Into<Upload>>(&self, file: U, base: &str) -> Result<Vec<u8>> {
+        self.query(api::multibase_transcode(file.into(), base))
+    }
+
+    pub fn name_publish(&self, req: &NamePublish) -> Result<NamePublishResponse> {
+        self.name_publish_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn name_publish_raw(&self, req: &NamePublish) -> Result<Vec<u8>> {
+        self.query(api::name_publish(req))
+    }
+
+    pub fn name_pubsub_cancel(&self, name: &str) -> Result<bool> {
+        self.name_pubsub_cancel_raw(name).and_then(|r| response::decode_bool(&r, "Canceled"))
+    }
+
+    pub fn name_pubsub_cancel_raw(&self, name: &str) -> Result<Vec<u8>> {
+        self.query(api::name_pubsub_cancel(name))
+    }
+
+    pub fn name_pubsub_state(&self) -> Result<bool> {
+        self.name_pubsub_state_raw().and_then(|r| response::decode_bool(&r, "Enabled"))
+    }
+
+    pub fn name_pubsub_state_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::name_pubsub_state())
+    }
+
+    pub fn name_pubsub_subs(&self) -> Result<Vec<String>> {
+        self.name_pubsub_subs_raw().and_then(|r| response::decode_strings(&r, "Strings"))
+    }
+
+    pub fn name_pubsub_subs_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::name_pubsub_subs())
+    }
+
+    pub fn name_resolve(&self, req: &NameResolve) -> Result<String> {
+        self.name_resolve_raw(req).and_then(|r| response::decode_string(&r, "Path"))
+    }
+
+    pub fn name_resolve_raw(&self, req: &NameResolve) -> Result<Vec<u8>> {
+        self.query(api::name_resolve(req))
+    }
+
+    pub fn object_data(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::object_data(key))
+    }
+
+    pub fn object_diff(&self, req: &ObjectDiff) -> Result<Vec<ObjectChange>> {
+        self.object_diff_raw(req).and_then(|r| response::decode_list(&r, "Changes"))
+    }
+
+    pub fn object_diff_raw(&self, req: &ObjectDiff) -> Result<Vec<u8>> {
+        self.query(api::object_diff(req))
+    }
+
+    pub fn object_get(&self, key: &str) -> Result<ObjectGetResponse> {
+        self.object_get_raw(key).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_get_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::object_get(key))
+    }
+
+    pub fn object_links(&self, req: &ObjectLinks) -> Result<ObjectResponse> {
+        self.object_links_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_links_raw(&self, req: &ObjectLinks) -> Result<Vec<u8>> {
+        self.query(api::object_links(req))
+    }
+
+    pub fn object_new(&self, obj: &str) -> Result<ObjectResponse> {
+        self.object_new_raw(obj).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_new_raw(&self, obj: &str) -> Result<Vec<u8>> {
+        self.query(api::object_new(obj))
+    }
+
+    pub fn object_patch_add_link(&self, req: &ObjectPatchAddLink) -> Result<ObjectResponse> {
+        self.object_patch_add_link_raw(req).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_patch_add_link_raw(&self, req: &ObjectPatchAddLink) -> Result<Vec<u8>> {
+        self.query(api::object_patch_add_link(req))
+    }
+
+    pub fn object_patch_append_data<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<ObjectResponse> {
+        self.object_patch_append_data_raw(hash, file).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_patch_append_data_raw<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<Vec<u8>> {
+        self.query(api::object_patch_append_data(hash, file.into()))
+    }
+
+    pub fn object_patch_rm_link(&self, hash: &str, iname: &str) -> Result<ObjectResponse> {
+        self.object_patch_rm_link_raw(hash, iname).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_patch_rm_link_raw(&self, hash: &str, iname: &str) -> Result<Vec<u8>> {
+        self.query(api::object_patch_rm_link(hash, iname))
+    }
+
+    pub fn object_patch_set_data<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<ObjectResponse> {
+        self.object_patch_set_data_raw(hash, file).and_then(|r| response::decode(&r))
+    }
+
+    pub fn object_patch_set_data_raw<U: Into<Upload>>(&self, hash: &str, file: U) -> Result<Vec<u8>> {
+        self.query(api::object_patch_set_data(hash, file.into()))
+    }
+
+    pub fn object_put<U: Into<Upload>>(&self, file: U, req: &ObjectPut) -> Result<ObjectResponse> {
+        self.object_put_raw(file, req).and_then(|r| response::decode(&r))
     }
 
-    pub fn bitswap_ledger(&mut self, peer: &str) -> Vec<u8> {
-        let l = format!("arg={}", peer);
-        self.url("/api/v0/bitswap/ledger").args(&l).query()
+    pub fn object_put_raw<U: Into<Upload>>(&self, file: U, req: &ObjectPut) -> Result<Vec<u8>> {
+        self.query(api::object_put(file.into(), req))
     }
 
-    pub fn bitswap_stat(&mut self) -> Vec<u8> {
-        self.url("/api/v0/bitswap/stat").query()
+    pub fn object_stat(&self, key: &str) -> Result<ObjectStatResponse> {
+        self.object_stat_raw(key).and_then(|r| response::decode(&r))
     }
 
-    pub fn bitswap_unwant(&mut self, key: &str) -> Vec<u8> {
-        let l = format!("arg={}", key);
-        self.url("/api/v0/bitswap/unwant").args(&l).query()
+    pub fn object_stat_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::object_stat(key))
     }
 
-    pub fn bitswap_wantlist(&mut self, peer: &str) -> Vec<u8> {
-        let l = format!("arg={}", peer);
-        self.url("/api/v0/bitswap/wantlist").args(&l).query()
+    pub fn pin_add(&self, req: &PinAdd) -> Result<PinResponse> {
+        self.pin_add_raw(req).and_then(|r| response::decode_lines(&r).and_then(last))
     }
 
-    pub fn block_get(&mut self, hash: &str) -> Vec<u8> {
-        let l = format!("arg={}", hash);
-        self.url("/api/v0/block/get").args(&l).query()
+    pub fn pin_add_raw(&self, req: &PinAdd) -> Result<Vec<u8>> {
+        self.query(api::pin_add(req))
     }
 
-    pub fn block_put(&mut self, raw_path: &str, fmt: &str, mhtype: &str, mhlen: i64) -> Vec<u8> {
-        let l = format!("format={}&mhtype={}&mhlen={}", fmt, mhtype, mhlen);
-        self.url("/api/v0/block/put").args(&l).path(raw_path).query_post_upload()
+    /// Like `pin_add`, but yields the number of nodes fetched so far as the pin goes,
+    /// ending with the pinned CIDs.
+    pub fn pin_add_progress(&self, req: &PinAdd) -> Result<JsonLines<PinResponse>> {
+        self.query_lines(api::pin_add_progress(req))
     }
 
-    pub fn block_rm(&mut self, hash: &str, force: bool, quiet: bool) -> Vec<u8> {
-        let f = &parse_bool(force);
-        let q = &parse_bool(quiet);
-        let l = format!("arg={}&force={}&quiet={}", hash, f, q);
-        self.url("/api/v0/block/rm").args(&l).query()
+    pub fn pin_ls(&self, req: &PinLs) -> Result<Vec<Pin>> {
+        self.pin_ls_raw(req).and_then(|r| response::decode_pins(&r))
     }
 
-    pub fn block_stat(&mut self, hash: &str) -> Vec<u8> {
-        let l = format!("arg={}", hash);
-        self.url("/api/v0/block/stat").args(&l).query()
+    pub fn pin_ls_raw(&self, req: &PinLs) -> Result<Vec<u8>> {
+        self.query(api::pin_ls(req))
     }
 
-    pub fn bootstrap_add_default(&mut self) -> Vec<u8> {
-        self.url("/api/v0/bootstrap/add/default").query()
+    pub fn pin_remote_add(&self, req: &PinRemoteAdd) -> Result<RemotePin> {
+        self.pin_remote_add_raw(req).and_then(|r| response::decode(&r))
     }
 
-    pub fn bootstrap_list(&mut self) -> Vec<u8> {
-        self.url("/api/v0/bootstrap/list").query()
+    pub fn pin_remote_add_raw(&self, req: &PinRemoteAdd) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_add(req))
     }
 
-    pub fn bootstrap_rm_all(&mut self) -> Vec<u8> {
-        self.url("/api/v0/bootstrap/rm/all").query()
+    pub fn pin_remote_ls(&self, req: &PinRemoteLs) -> Result<Vec<RemotePin>> {
+        self.pin_remote_ls_raw(req).and_then(|r| response::decode_lines(&r))
     }
 
-    pub fn cat(&mut self, hash: &str) -> Vec<u8> {
-        let l = format!("arg={}", hash);
-        self.url("/api/v0/cat").args(&l).query()
+    pub fn pin_remote_ls_raw(&self, req: &PinRemoteLs) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_ls(req))
     }
 
-    pub fn commands(&mut self) -> Vec<u8> {
-        self.url("/api/v0/commands").query()
+    pub fn pin_remote_rm(&self, req: &PinRemoteRm) -> Result<()> {
+        self.pin_remote_rm_raw(req).map(|_| ())
     }
 
-    pub fn config_replace(&mut self, raw_path: &str) -> Vec<u8> {
-        self.url("/api/v0/config/replace").path(raw_path).query_post_upload()
+    pub fn pin_remote_rm_raw(&self, req: &PinRemoteRm) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_rm(req))
     }
 
-    pub fn config_show(&mut self) -> Vec<u8> {
-        self.url("/api/v0/config/show").query()
+    pub fn pin_remote_service_add(&self, service: &str, endpoint: &str, key: &str) -> Result<()> {
+        self.pin_remote_service_add_raw(service, endpoint, key).map(|_| ())
     }
 
-    pub fn dag_get(&mut self, inp: &str) -> Vec<u8> {
-        let l = format!("arg={}", inp);
-        self.url("/api/v0/dag/get").args(&l).query()
+    pub fn pin_remote_service_add_raw(&self, service: &str, endpoint: &str, key: &str) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_service_add(service, endpoint, key))
     }
 
-    pub fn dag_put(&mut self, raw_path: &str, fmt: &str, ienc: &str) -> Vec<u8> {
-        let l = format!("format={}&input-enc={}", fmt, ienc);
-        self.url("/api/v0/dag/put").args(&l).path(raw_path).query_post_upload()
+    pub fn pin_remote_service_ls(&self) -> Result<Vec<PinRemoteService>> {
+        self.pin_remote_service_ls_raw().and_then(|r| response::decode_list(&r, "RemoteServices"))
     }
 
-    pub fn dht_findpeer(&mut self, peer: &str, verbose: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        let l = format!("arg={}&verbose={}", peer, v);
-        self.url("/api/v0/dht/findpeer").args(&l).query()
+    pub fn pin_remote_service_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_service_ls())
     }
 
-    pub fn dht_findprovs(&mut self, key: &str, verbose: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        let l = format!("arg={}&verbose={}", key, v);
-        self.url("/api/v0/dht/findprovs").args(&l).query()
+    pub fn pin_remote_service_rm(&self, service: &str) -> Result<()> {
+        self.pin_remote_service_rm_raw(service).map(|_| ())
     }
 
-    pub fn dht_get(&mut self, key: &str, verbose: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        let l = format!("arg={}&verbose={}", key, v);
-        self.url("/api/v0/dht/get").args(&l).query()
+    pub fn pin_remote_service_rm_raw(&self, service: &str) -> Result<Vec<u8>> {
+        self.query(api::pin_remote_service_rm(service))
     }
 
-    pub fn dht_provide(&mut self, key: &str, verbose: bool, recursive: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        let r = &parse_bool(recursive);
-        let l = format!("arg={}&verbose={}&recursive={}", key, v, r);
-        self.url("/api/v0/dht/provide").args(&l).query()
+    pub fn pin_rm(&self, req: &PinRm) -> Result<PinResponse> {
+        self.pin_rm_raw(req).and_then(|r| response::decode(&r))
     }
 
-    pub fn dht_put(&mut self, key: &str, val: &str, verbose: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        // key goes first, then goes value and it will be stored as a dict mem.
-        let l = format!("arg={}&arg={}&verbose={}", key, val, v);
-        self.url("/api/v0/dht/put").args(&l).query()
+    pub fn pin_rm_raw(&self, req: &PinRm) -> Result<Vec<u8>> {
+        self.query(api::pin_rm(req))
     }
 
-    pub fn dht_query(&mut self, peer: &str, verbose: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        let l = format!("arg={}&verbose={}", peer, v);
-        self.url("/api/v0/dht/query").args(&l).query()
+    pub fn pin_update(&self, req: &PinUpdate) -> Result<PinResponse> {
+        self.pin_update_raw(req).and_then(|r| response::decode(&r))
     }
 
-    pub fn diag_cmds_clear(&mut self) -> Vec<u8> {
-        self.url("/api/v0/diag/cmds/clear").query()
+    pub fn pin_update_raw(&self, req: &PinUpdate) -> Result<Vec<u8>> {
+        self.query(api::pin_update(req))
     }
 
-    pub fn diag_set_time(&mut self, ntime: &str) -> Vec<u8> {
-        let l = format!("arg={}", ntime);
-        self.url("/api/v0/diag/cmds/set-time").args(&l).query()
+    pub fn pin_verify(&self, req: &PinVerify) -> Result<Vec<PinVerifyResponse>> {
+        self.pin_verify_raw(req).and_then(|r| response::decode_lines(&r))
     }
 
-    pub fn diag_net(&mut self, vis: &str) -> Vec<u8> {
-        let l = format!("vis={}", vis);
-        self.url("/api/v0/diag/net").args(&l).query()
+    pub fn pin_verify_raw(&self, req: &PinVerify) -> Result<Vec<u8>> {
+        self.query(api::pin_verify(req))
     }
 
-    pub fn diag_sys(&mut self) -> Vec<u8> {
-        self.url("/api/v0/diag/sys").query()
+    /// Yields each line of the ping run as it happens. Feed them to a `PingSummary` for
+    /// latency statistics, or see `ping_summary`.
+    pub fn ping(&self, req: &Ping) -> Result<JsonLines<PingResponse>> {
+        self.query_lines(api::ping(req))
     }
 
-    pub fn dns(&mut self, link: &str, recursive: bool) -> Vec<u8> {
-        let r = &parse_bool(recursive);
-        let l = format!("arg={}&recursive={}", link, r);
-        self.url("/api/v0/dns").args(&l).query()
+    /// Runs the whole ping and sums up the results.
+    pub fn ping_summary(&self, req: &Ping) -> Result<PingSummary> {
+        let mut summary = PingSummary::new();
+        for ping in self.ping(req)? {
+            summary.record(&ping?);
+        }
+        Ok(summary)
+    }
+
+    pub fn ping_raw(&self, req: &Ping) -> Result<Vec<u8>> {
+        self.query(api::ping(req))
+    }
+
+    pub fn pubsub_ls(&self) -> Result<Vec<String>> {
+        let encoding = self.pubsub_encoding();
+        self.pubsub_ls_raw().and_then(|r| response::decode_topics(&r, encoding))
+    }
+
+    pub fn pubsub_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::pubsub_ls())
     }
 
-    pub fn file_ls(&mut self, ipath: &str) -> Vec<u8> {
-        let l = format!("arg={}", ipath);
-        self.url("/api/v0/file/ls").args(&l).query()
+    pub fn pubsub_peers(&self, topic: &str) -> Result<Vec<String>> {
+        self.pubsub_peers_raw(topic).and_then(|r| response::decode_strings(&r, "Strings"))
     }
 
-    pub fn files_cp(&mut self, from: &str, to: &str) -> Vec<u8> {
-        let l = format!("arg={}&arg={}", from, to);
-        self.url("/api/v0/files/cp").args(&l).query()
+    pub fn pubsub_peers_raw(&self, topic: &str) -> Result<Vec<u8>> {
+        self.query(api::pubsub_peers(topic, self.topic_encoding()))
     }
 
-    pub fn files_flush(&mut self, ipath: &str) -> Vec<u8> {
-        let l = format!("arg={}", ipath);
-        self.url("/api/v0/files/flush").args(&l).query()
+    /// Publishes `data` to `topic`. Binary data is fine: it goes in the request body.
+    pub fn pubsub_pub<D: Into<Upload>>(&self, topic: &str, data: D) -> Result<()> {
+        self.pubsub_pub_raw(topic, data).map(|_| ())
     }
 
-    pub fn files_ls(&mut self, ipath: &str, ilong: bool) -> Vec<u8> {
-        let long = &parse_bool(ilong);
-        let l = format!("arg={}&long={}", ipath, long);
-        self.url("/api/v0/files/flush").args(&l).query()
+    pub fn pubsub_pub_raw<D: Into<Upload>>(&self, topic: &str, data: D) -> Result<Vec<u8>> {
+        self.query(api::pubsub_pub(topic, data.into(), self.topic_encoding()))
     }
 
-    pub fn files_mkdir(&mut self, ipath: &str, prnts: bool) -> Vec<u8> {
-        let p = &parse_bool(prnts);
-        let l = format!("arg={}&parents={}", ipath, p);
-        self.url("/api/v0/files/mkdir").args(&l).query()
+    /// Subscribes to `topic`, yielding messages as they arrive until the iterator is dropped.
+    pub fn pubsub_sub(&self, topic: &str, discover: bool) -> Result<JsonLines<PubsubMessage>> {
+        let encoding = self.pubsub_encoding();
+        let decode = response::pubsub_decoder(encoding);
+        let encoding = encoding.unwrap_or(PubsubEncoding::Multibase);
+        Ok(JsonLines::new(self.query_reader(api::pubsub_sub(topic, discover, encoding))?, decode))
     }
 
-    pub fn files_mv(&mut self, source: &str, dest: &str) -> Vec<u8> {
-        let l = format!("arg={}&arg={}", source, dest);
-        self.url("/api/v0/files/mv").args(&l).query()
+    /// Like `pubsub_sub`, but hands back the daemon's reply untouched.
+    pub fn pubsub_sub_reader(&self, topic: &str, discover: bool) -> Result<ResponseReader> {
+        self.query_reader(api::pubsub_sub(topic, discover, self.topic_encoding()))
     }
 
-    pub fn files_read(&mut self, ipath: &str, off: i64, count: i64) -> Vec<u8> {
-        let l = format!("arg={}&offset={}&count={}", ipath, &off.to_string(), &count.to_string());
-        self.url("/api/v0/files/read").args(&l).query()
+    pub fn refs_local(&self) -> Result<Vec<RefResponse>> {
+        self.refs_local_raw().and_then(|r| response::decode_lines(&r))
     }
 
-    pub fn files_rm(&mut self, ipath: &str, recursive: bool) -> Vec<u8> {
-        let r = &parse_bool(recursive);
-        let l = format!("arg={}&recursive={}", ipath, r);
-        self.url("/api/v0/files/rm").args(&l).query()
+    pub fn refs_local_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::refs_local())
     }
 
-    pub fn files_stat(&mut self, ipath: &str, fmt: &str, hash: bool, size: bool) -> Vec<u8> {
-        let h = &parse_bool(hash);
-        let s = &parse_bool(size);
-        let l = format!("arg={}&format={}&hash={}&size={}", ipath, fmt, h, s);
-        self.url("/api/v0/files/stat").args(&l).query()
+    pub fn repo_fsck(&self) -> Result<String> {
+        self.repo_fsck_raw().and_then(|r| response::decode_string(&r, "Message"))
     }
 
-    pub fn files_write(&mut self, ipath: &str, raw_path: &str, off: i64,
-                        create: bool, truncate: bool, count: i64) -> Vec<u8> {
-        let c = &parse_bool(create);
-        let t = &parse_bool(truncate);
-        let l = format!("arg={}&arg={}&offset={}&create={}&truncate={}&count={}",
-                        ipath, raw_path, &off.to_string(), c, t, &count.to_string());
-        self.url("/api/v0/files/write").args(&l).query_post_upload()
+    pub fn repo_fsck_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::repo_fsck())
     }
 
-    pub fn filestore_dups(&mut self) -> Vec<u8> {
-        self.url("/api/v0/filestore/dups").query()
+    pub fn repo_gc(&self, req: &RepoGc) -> Result<Vec<RepoGcResponse>> {
+        self.repo_gc_raw(req).and_then(|r| response::decode_lines(&r))
     }
 
-    pub fn filestore_ls(&mut self, cid: &str) -> Vec<u8> {
-        let l = format!("arg={}", cid);
-        self.url("/api/v0/filestore/ls").args(&l).query()
+    pub fn repo_gc_raw(&self, req: &RepoGc) -> Result<Vec<u8>> {
+        self.query(api::repo_gc(req))
     }
 
-    pub fn filestore_verify(&mut self, cid: &str) -> Vec<u8> {
-        let l = format!("arg={}", cid);
-        self.url("/api/v0/filestore/verify").args(&l).query()
+    pub fn repo_stat(&self, req: &RepoStat) -> Result<RepoStatResponse> {
+        self.repo_stat_raw(req).and_then(|r| response::decode(&r))
     }
 
-    pub fn get(&mut self, ipath: &str,
-                raw_path: &str, archive: bool,
-                compress: bool, clevel: i8) -> Vec<u8> {
-        let a = &parse_bool(archive);
-        let c = &parse_bool(compress);
-        let l = format!("arg={}&arg={}&archive={}&compress={}&compression-level={}",
-                        ipath, raw_path, a, c, &clevel.to_string());
-        self.url("/api/v0/get").args(&l).query()
+    pub fn repo_stat_raw(&self, req: &RepoStat) -> Result<Vec<u8>> {
+        self.query(api::repo_stat(req))
     }
 
-    pub fn id(&mut self, peer: &str, fmt: &str) -> Vec<u8> {
-        let l = format!("arg={}&format={}", peer, fmt);
-        self.url("/api/v0/id").args(&l).query()
+    pub fn repo_verify(&self) -> Result<Vec<RepoVerifyResponse>> {
+        self.repo_verify_raw().and_then(|r| response::decode_lines(&r))
     }
 
-    pub fn key_gen(&mut self, name: &str, itype: &str, size: i64) -> Vec<u8> {
-        let l = format!("arg={}&type={}&size={}", name, itype, &size.to_string());
-        self.url("/api/v0/key/gen").args(&l).query()
+    pub fn repo_verify_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::repo_verify())
     }
 
-    pub fn key_list(&mut self, extra: bool) -> Vec<u8> {
-        let e = &parse_bool(extra);
-        let l = format!("l={}", e);
-        self.url("/api/v0/key/list").args(&l).query()
+    pub fn repo_version(&self, req: &RepoVersion) -> Result<String> {
+        self.repo_version_raw(req).and_then(|r| response::decode_string(&r, "Version"))
     }
 
-    pub fn log_level(&mut self, sli: &str, level: &str) -> Vec<u8> {
-        let l = format!("arg={}&arg={}", sli, level);
-        self.url("/api/v0/log/level").args(&l).query()
+    pub fn repo_version_raw(&self, req: &RepoVersion) -> Result<Vec<u8>> {
+        self.query(api::repo_version(req))
     }
 
-    pub fn log_ls(&mut self) -> Vec<u8> {
-        self.url("/api/v0/log/ls").query()
+    pub fn resolve(&self, req: &Resolve) -> Result<String> {
+        self.resolve_raw(req).and_then(|r| response::decode_string(&r, "Path"))
     }
 
-    pub fn log_tail(&mut self) -> Vec<u8> {
-        self.url("/api/v0/log/tail").query()
+    pub fn resolve_raw(&self, req: &Resolve) -> Result<Vec<u8>> {
+        self.query(api::resolve(req))
     }
 
-    pub fn ls(&mut self, ipath: &str, hdrs: bool, rtype: bool) -> Vec<u8> {
-        let h = &parse_bool(hdrs);
-        let r = &parse_bool(rtype);
-        let l = format!("arg={}&headers={}&resolve-type={}", ipath, h, r);
-        self.url("/api/v0/ls").args(&l).query()
+    /// Like `dht_findpeer`, but asks every configured router rather than only the DHT.
+    pub fn routing_findpeer(&self, peer: &str) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::routing_findpeer(peer))
     }
 
-    pub fn mount(&mut self, ipfs_path: &str, ipns_path: &str) -> Vec<u8> {
-        let l = format!("ipfs-path={}&ipns-path={}", ipfs_path, ipns_path);
-        self.url("/api/v0/mount").args(&l).query()
+    pub fn routing_findpeer_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::routing_findpeer(peer))
     }
 
-    pub fn name_publish(&mut self, ipath: &str,
-                        resolve: bool, lifetime: &str,
-                        ttl: &str, key: &str) -> Vec<u8> {
-        let r = &parse_bool(resolve);
-        let l = format!("arg={}&resolve={}&lifetime={}&ttl={}&key={}",
-                ipath, r, lifetime, ttl, key);
-        self.url("/api/v0/name/publish").args(&l).query()
+    pub fn routing_findprovs(&self, key: &str, num_providers: Option<u32>) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::routing_findprovs(key, num_providers))
     }
 
-    pub fn name_resolve(&mut self, iname: &str, recursive: bool, nocache: bool) -> Vec<u8> {
-        let r = &parse_bool(recursive);
-        let n = &parse_bool(nocache);
-        let l = format!("arg={}&recursive={}&nocache={}", iname, r, n);
-        self.url("/api/v0/name/resolve").args(&l).query()
+    pub fn routing_findprovs_raw(&self, key: &str, num_providers: Option<u32>) -> Result<Vec<u8>> {
+        self.query(api::routing_findprovs(key, num_providers))
     }
 
-    pub fn object_data(&mut self, key: &str) -> Vec<u8> {
-        let l = format!("arg={}", key);
-        self.url("/api/v0/object/data").args(&l).query()
+    pub fn routing_get(&self, key: &str) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::routing_get(key))
     }
 
-    pub fn object_diff(&mut self, left: &str, right: &str, verbose: bool) -> Vec<u8> {
-        let v = &parse_bool(verbose);
-        let l = format!("arg={}&arg={}&verbose={}", left, right, v);
-        self.url("/api/v0/object/diff").args(&l).query()
+    pub fn routing_get_raw(&self, key: &str) -> Result<Vec<u8>> {
+        self.query(api::routing_get(key))
     }
 
-    pub fn object_get(&mut self, key: &str) -> Vec<u8> {
-        let l = format!("arg={}", key);
-        self.url("/api/v0/object/get").args(&l).query()
+    pub fn routing_provide(&self, req: &DhtProvide) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::routing_provide(req))
     }
 
-    pub fn object_links(&mut self, key: &str, hdrs: bool) -> Vec<u8> {
-        let h = &parse_bool(hdrs);
-        let l = format!("arg={}&headers={}", key, h);
-        self.url("/api/v0/object/links").args(&l).query()
+    pub fn routing_provide_raw(&self, req: &DhtProvide) -> Result<Vec<u8>> {
+        self.query(api::routing_provide(req))
     }
 
-    pub fn object_new(&mut self, obj: &str) -> Vec<u8> {
-        let l = format!("arg={}", obj);
-        self.url("/api/v0/object/new").args(&l).query()
+    /// Stores `value` under `key`, which must be an IPNS or other signed record.
+    pub fn routing_put<U: Into<Upload>>(&self, key: &str, value: U) -> Result<JsonLines<DhtEvent>> {
+        self.query_lines(api::routing_put(key, value.into()))
     }
 
-    pub fn object_patch_add_link(&mut self, hash: &str, iname: &str, iobj: &str, create: bool) -> Vec<u8> {
-        let c = &parse_bool(create);
-        let l = format!("arg={}&arg={}&arg={}&create={}", hash, iname, iobj, c);
-        self.url("/api/v0/object/patch/add-link").args(&l).query()
+    pub fn routing_put_raw<U: Into<Upload>>(&self, key: &str, value: U) -> Result<Vec<u8>> {
+        self.query(api::routing_put(key, value.into()))
     }
 
-    pub fn object_patch_append_data(&mut self, hash: &str, raw_path: &str) -> Vec<u8> {
-        let l = format!("arg={}", hash);
-        self.url("/api/v0/object/patch/append-data").args(&l).path(raw_path).query_post_upload()
+    /// Stops the daemon.
+    pub fn shutdown(&self) -> Result<()> {
+        self.shutdown_raw().map(|_| ())
     }
 
-    pub fn object_patch_rm_link(&mut self, hash: &str, iname: &str) -> Vec<u8> {
-        let l = format!("arg={}&arg={}", hash, iname);
-        self.url("/api/v0/object/patch/rm-link").args(&l).query()
+    pub fn shutdown_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::shutdown())
     }
 
-    pub fn object_patch_set_data(&mut self, hash: &str, raw_path: &str) -> Vec<u8> {
-        let l = format!("arg={}", hash);
-        self.url("/api/v0/object/patch/set-data").args(&l).path(raw_path).query_post_upload()
+    pub fn stats_bitswap(&self) -> Result<BitswapStatResponse> {
+        self.stats_bitswap_raw().and_then(|r| response::decode(&r))
     }
 
-    pub fn object_put(&mut self, raw_path: &str, ienc: &str, dfenc: &str) -> Vec<u8> {
-        let l = format!("inputenc={}&datafieldenc={}", ienc, dfenc);
-        self.url("/api/v0/object/put").args(&l).path(raw_path).query_post_upload()
+    pub fn stats_bitswap_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::stats_bitswap())
     }
 
-    pub fn object_stat(&mut self, key: &str) -> Vec<u8> {
-        let l = format!("arg={}", key);
-        self.url("/api/v0/object/stat").args(&l).query()
+    pub fn stats_bw(&self, req: &StatsBw) -> Result<Vec<BandwidthStats>> {
+        self.stats_bw_raw(req).and_then(|r| response::decode_lines(&r))
     }
 
-    pub fn pin_add(&mut self, ipath: &str, recursive: bool, progress: bool) -> Vec<u8> {
-        let r = &parse_bool(recursive);
-        let p = &parse_bool(progress);
-        let l = format!("arg={}&recursive={}&progress={}", ipath, r, p);
-        self.url("/api/v0/pin/add").args(&l).query()
+    pub fn stats_bw_raw(&self, req: &StatsBw) -> Result<Vec<u8>> {
+        self.query(api::stats_bw(req))
     }
 
-    pub fn pin_ls(&mut self, ipath: &str, itype: &str, quiet: bool) -> Vec<u8> {
-        let q = &parse_bool(quiet);
-        let l = format!("arg={}&type={}&quiet={}", ipath, itype, q);
-        self.url("/api/v0/pin/ls").args(&l).query()
+    /// Samples bandwidth every `interval` until the iterator is dropped.
+    pub fn stats_bw_poll(&self, req: &StatsBw, interval: Duration) -> Result<JsonLines<BandwidthStats>> {
+        self.query_lines(api::stats_bw_poll(req, interval))
     }
 
-    pub fn pin_rm(&mut self, ipath: &str, recursive: bool) -> Vec<u8> {
-        let r = &parse_bool(recursive);
-        let l = format!("arg={}&recursive={}", ipath, r);
-        self.url("/api/v0/pin/rm").args(&l).query()
+    pub fn stats_repo(&self, req: &RepoStat) -> Result<RepoStatResponse> {
+        self.stats_repo_raw(req).and_then(|r| response::decode(&r))
     }
 
-    pub fn ping(&mut self, peer: &str, count: i32) -> Vec<u8> {
-        let l = format!("arg={}&count={}", peer, &count.to_string());
-        self.url("/api/v0/ping").args(&l).query()
+    pub fn stats_repo_raw(&self, req: &RepoStat) -> Result<Vec<u8>> {
+        self.query(api::stats_repo(req))
     }
 
-    pub fn pubsub_ls(&mut self) -> Vec<u8> {
-        self.url("/api/v0/pubsub/ls").query()
+    pub fn swarm_addrs_local(&self, peer: &str) -> Result<Vec<String>> {
+        self.swarm_addrs_local_raw(peer).and_then(|r| response::decode_strings(&r, "Strings"))
     }
 
-    pub fn pubsub_peers(&mut self, topic: &str) -> Vec<u8> {
-        let l = format!("arg={}", topic);
-        self.url("/api/v0/pubsub/peers").args(&l).query()
+    pub fn swarm_addrs_local_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_addrs_local(peer))
     }
 
-    pub fn pubsub_pub(&mut self, topic: &str, payload: &str) -> Vec<u8> {
-        let l = format!("arg={}&payload={}", topic, payload);
-        self.url("/api/v0/pubsub/pub").args(&l).query()
+    pub fn swarm_connect(&self, addrs: &str) -> Result<Vec<String>> {
+        self.swarm_connect_raw(addrs).and_then(|r| response::decode_strings(&r, "Strings"))
     }
 
-    pub fn pubsub_sub(&mut self, topic: &str, discover: bool) -> Vec<u8> {
-        let d = &parse_bool(discover);
-        let l = format!("arg={}&discover={}", topic, d);
-        self.url("/api/v0/pubsub/sub").args(&l).query()
+    pub fn swarm_connect_raw(&self, addrs: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_connect(addrs))
     }
 
-    pub fn refs_local(&mut self) -> Vec<u8> {
-        self.url("/api/v0/refs/local").query()
+    pub fn swarm_disconnect(&self, addrs: &str) -> Result<Vec<String>> {
+        self.swarm_disconnect_raw(addrs).and_then(|r| response::decode_strings(&r, "Strings"))
     }
 
-    pub fn repo_fsck(&mut self) -> Vec<u8> {
-        self.url("/api/v0/repo/fsck").query()
+    pub fn swarm_disconnect_raw(&self, addrs: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_disconnect(addrs))
     }
 
-    pub fn repo_gc(&mut self, quiet: bool, serr: bool) -> Vec<u8> {
-        let q = &parse_bool(quiet);
-        let s = &parse_bool(serr);
-        let l = format!("quiet={}&stream-errors={}", q, s);
-        self.url("/api/v0/repo/gc").args(&l).query()
+    pub fn swarm_filters_add(&self, maddr: &str) -> Result<Vec<String>> {
+        self.swarm_filters_add_raw(maddr).and_then(|r| response::decode_strings(&r, "Strings"))
     }
 
-    pub fn repo_stat(&mut self, human: bool) -> Vec<u8> {
-        let h = &parse_bool(human);
-        let l = format!("human={}", h);
-        self.url("/api/v0/repo/stat").args(&l).query()
+    pub fn swarm_filters_add_raw(&self, maddr: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_filters_add(maddr))
     }
 
-    pub fn repo_verify(&mut self) -> Vec<u8> {
-        self.url("/api/v0/repo/verify").query()
+    pub fn swarm_filderst_rm(&self, maddr: &str) -> Result<Vec<String>> {
+        self.swarm_filderst_rm_raw(maddr).and_then(|r| response::decode_strings(&r, "Strings"))
     }
 
-    pub fn repo_version(&mut self, quiet: bool) -> Vec<u8> {
-        let q = &parse_bool(quiet);
-        let l = format!("quiet={}", q);
-        self.url("/api/v0/repo/version").args(&l).query()
+    pub fn swarm_filderst_rm_raw(&self, maddr: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_filderst_rm(maddr))
     }
 
-    pub fn resolve(&mut self, iname: &str, recursive: bool) -> Vec<u8> {
-        let r = &parse_bool(recursive);
-        let l = format!("arg={}&recursive={}", iname, r);
-        self.url("/api/v0/resolve").args(&l).query()
+    pub fn swarm_peering_add(&self, addr: &str) -> Result<PeeringResponse> {
+        self.swarm_peering_add_raw(addr).and_then(|r| response::decode(&r))
     }
 
-    pub fn stats_bitswap(&mut self) -> Vec<u8> {
-        self.url("/api/v0/stats/bitswap").query()
+    pub fn swarm_peering_add_raw(&self, addr: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_peering_add(addr))
     }
 
-    pub fn stats_bw(&mut self, peer: &str, proto: &str, poll: bool, interval: &str) -> Vec<u8> {
-        let p = &parse_bool(poll);
-        let l = format!("peer={}&proto={}&poll={}&interval={}", peer, proto, p, interval);
-        self.url("/api/v0/stats/bw").args(&l).query()
+    pub fn swarm_peering_ls(&self) -> Result<Vec<DhtPeer>> {
+        self.swarm_peering_ls_raw().and_then(|r| response::decode_list(&r, "Peers"))
     }
 
-    pub fn stats_repo(&mut self, human: bool) -> Vec<u8> {
-        let h = &parse_bool(human);
-        let l = format!("human={}", h);
-        self.url("/api/v0/stats/repo").args(&l).query()
+    pub fn swarm_peering_ls_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::swarm_peering_ls())
     }
 
-    pub fn swarm_addrs_local(&mut self, peer: &str) -> Vec<u8> {
-        let l = format!("arg={}", peer);
-        self.url("/api/v0/swarm/addrs/local").args(&l).query()
+    pub fn swarm_peering_rm(&self, peer: &str) -> Result<PeeringResponse> {
+        self.swarm_peering_rm_raw(peer).and_then(|r| response::decode(&r))
     }
 
-    pub fn swarm_connect(&mut self, addrs: &str) -> Vec<u8> {
-        let l = format!("arg={}", addrs);
-        self.url("/api/v0/swarm/connect").args(&l).query()
+    pub fn swarm_peering_rm_raw(&self, peer: &str) -> Result<Vec<u8>> {
+        self.query(api::swarm_peering_rm(peer))
     }
 
-    pub fn swarm_disconnect(&mut self, addrs: &str) -> Vec<u8> {
-        let l = format!("arg={}", addrs);
-        self.url("/api/v0/swarm/disconnect").args(&l).query()
+    pub fn swarm_peers(&self) -> Result<Vec<SwarmPeer>> {
+        self.swarm_peers_raw().and_then(|r| response::decode_list(&r, "Peers"))
     }
 
-    pub fn swarm_filters_add(&mut self, maddr: &str) -> Vec<u8> {
-        let l = format!("arg={}", maddr);
-        self.url("/api/v0/swarm/filters/add").args(&l).query()
+    pub fn swarm_peers_raw(&self) -> Result<Vec<u8>> {
+        self.query(api::swarm_peers())
     }
 
-    pub fn swarm_filderst_rm(&mut self, maddr: &str) -> Vec<u8> {
-        let l = format!("arg={}", maddr);
-        self.url("/api/v0/swarm/filters/rm").args(&l).query()
+    pub fn tar_add<U: Into<Upload>>(&self, file: U) -> Result<AddResponse> {
+        self.tar_add_raw(file).and_then(|r| response::decode(&r))
     }
 
-    pub fn swarm_peers(&mut self) -> Vec<u8> {
-        self.url("/api/v0/swarm/peers").query()
+    pub fn tar_add_raw<U: Into<Upload>>(&self, file: U) -> Result<Vec<u8>> {
+        self.query(api::tar_add(file.into()))
     }
 
-    pub fn tar_add(&mut self, raw_path: &str) -> Vec<u8> {
-        self.url("/api/v0/tar/add").path(raw_path).query_post_upload()
+    pub fn tar_cat(&self, ipath: &str) -> Result<Vec<u8>> {
+        self.query(api::tar_cat(ipath))
     }
 
-    pub fn tar_cat(&mut self, ipath: &str) -> Vec<u8> {
-        let l = format!("arg={}", ipath);
-        self.url("/api/v0/tar/cat").args(&l).query()
+    pub fn tour_list(&self) -> Result<Vec<u8>> {
+        self.query(api::tour_list())
     }
 
-    pub fn tour_list(&mut self) -> Vec<u8> {
-        self.url("/api/v0/tour/list").query()
+    pub fn tour_next(&self) -> Result<Vec<u8>> {
+        self.query(api::tour_next())
     }
 
-    pub fn tour_next(&mut self) -> Vec<u8> {
-        self.url("/api/v0/tour/next").query()
+    pub fn tour_restart(&self) -> Result<Vec<u8>> {
+        self.query(api::tour_restart())
     }
 
-    pub fn tour_restart(&mut self) -> Vec<u8> {
-        self.url("/api/v0/tour/restart").query()
+    pub fn update(&self, sargs: &str) -> Result<Vec<u8>> {
+        self.query(api::update(sargs))
     }
 
-    pub fn update(&mut self, sargs: &str) -> Vec<u8> {
-        let l = format!("arg={}", sargs);
-        self.url("/api/v0/update").args(&l).query()
+    pub fn version(&self, req: &Version) -> Result<VersionResponse> {
+        self.version_raw(req).and_then(|r| response::decode(&r))
     }
 
-    pub fn version(&mut self, num: bool, com: bool, repo: bool, all: bool) -> Vec<u8> {
-        let n = &parse_bool(num);
-        let c = &parse_bool(com);
-        let r = &parse_bool(repo);
-        let a = &parse_bool(all);
-        let l = format!("number={}&commit={}&repo={}&all={}", n, c, r, a);
-        self.url("/api/v0/version").args(&l).query()
+    pub fn version_raw(&self, req: &Version) -> Result<Vec<u8>> {
+        self.query(api::version(req))
     }
 
 
     //------------------------------------------- QUERIES -----------------------------------------
 
 
-    fn complete_post_link(&self) -> String {
-        let link = if self.url.chars().nth(0).unwrap() == '/' {
-            format!("{}", &self.url)
-        } else {
-            format!("/{}", &self.url)
-        };
-        self.host.to_string() + ":" + &self.port.to_string() + &link
+    fn request_capabilities(&self) -> Result<DaemonCapabilities> {
+        let mut version = Vec::new();
+        transfer::perform_into(self.prepare_unchecked(api::version(&Version::new()))?, &mut version)?;
+        let mut commands = Vec::new();
+        transfer::perform_into(self.prepare_unchecked(api::commands())?, &mut commands)?;
+        Ok(DaemonCapabilities::new(response::decode(&version)?, &CommandTree::new(response::decode(&commands)?)))
     }
 
-    fn complete_get_link(&self) -> String {
-        let l = self.complete_post_link();
-        if !&self.args.is_empty() {
-            return format!("{}?{}", l, &self.args)
+    /// Fetches the capabilities and remembers whether that worked.
+    fn fetch_capabilities(&self) -> Result<Arc<DaemonCapabilities>> {
+        match self.request_capabilities() {
+            Ok(caps) => Ok(self.capabilities.set(caps)),
+            Err(e) => {
+                self.capabilities.set_failed(&e);
+                Err(e)
+            }
         }
-        return l
     }
 
-    fn query(&self) -> Vec<u8> {
-        let mut data = Vec::new();
-        let mut handle = Easy::new();
-        let l = &self.complete_get_link();
-        handle.url(l).unwrap();
-        {
-            let mut transfer = handle.transfer();
-            transfer.write_function(|new_data| {
-                data.extend_from_slice(new_data);
-                Ok(new_data.len())
-            }).unwrap();
-            transfer.perform().unwrap();
+    /// The capabilities, fetched on first use, or `None` if they could not be.
+    fn known_capabilities(&self) -> Option<Arc<DaemonCapabilities>> {
+        match self.capabilities.get() {
+            Some(Fetched::Known(caps)) => Some(caps),
+            Some(Fetched::Unknown { .. }) => None,
+            None => self.fetch_capabilities().ok(),
         }
-        data
-    }
-
-    fn query_post_upload(&self) -> Vec<u8> {
-        let mut buffer = Vec::new();
-        let mut handle = Easy::new();
-        let mut frm    = Form::new();
-        let path       = Path::new(&self.path);
-        frm.part("arg").file(path).add().unwrap();
-        handle.url(&self.complete_get_link()).unwrap();
-        handle.httppost(frm).unwrap();
-        {
-            let mut transfer = handle.transfer();
-            transfer.write_function(|new_data| {
-                buffer.extend_from_slice(new_data);
-                Ok(new_data.len())
-            }).unwrap();
-            transfer.perform().unwrap();
+    }
+
+    /// How the daemon encodes pubsub data, if its version is known.
+    fn pubsub_encoding(&self) -> Option<PubsubEncoding> {
+        self.known_capabilities().map(|caps| caps.pubsub_encoding())
+    }
+
+    /// How to send topic names: as the daemon expects them, or as current daemons do.
+    fn topic_encoding(&self) -> PubsubEncoding {
+        self.pubsub_encoding().unwrap_or(PubsubEncoding::Multibase)
+    }
+
+    /// Refuses calls the daemon does not serve. When its capabilities cannot be fetched the call
+    /// is sent anyway, and fails or not on its own.
+    fn prepare(&self, req: Request) -> Result<Call> {
+        if let Some(caps) = self.known_capabilities() {
+            caps.check(req.command())?;
         }
-        buffer
+        self.prepare_unchecked(req)
+    }
+
+    fn prepare_unchecked(&self, req: Request) -> Result<Call> {
+        let mut handle = self.pool.take();
+        handle.url(&req.link(&self.endpoint))?;
+        if let Some(socket) = self.endpoint.socket() {
+            handle.unix_socket_path(Some(socket))?;
+        }
+        let body = match req.body {
+            Some(form) => {
+                let mut headers = List::new();
+                headers.append(&format!("Content-Type: {}", form.content_type()))?;
+                // Readers have no known length up front.
+                headers.append("Transfer-Encoding: chunked")?;
+                handle.http_headers(headers)?;
+                handle.post(true)?;
+                Some(form.into_reader()?)
+            }
+            None => {
+                // The daemon only answers POST, even for calls that upload nothing.
+                handle.post(true)?;
+                handle.post_field_size(0)?;
+                None
+            }
+        };
+        Ok(Call { handle, body, pool: self.pool.clone() })
+    }
+
+    fn query(&self, req: Request) -> Result<Vec<u8>> {
+        let mut data = Vec::new();
+        self.query_to(req, &mut data)?;
+        Ok(data)
+    }
+
+    fn query_to<W: Write + ?Sized>(&self, req: Request, out: &mut W) -> Result<u64> {
+        transfer::perform_into(self.prepare(req)?, out)
+    }
+
+    fn query_reader(&self, req: Request) -> Result<ResponseReader> {
+        ResponseReader::spawn(self.prepare(req)?)
+    }
+
+    fn query_lines<T: FromJson>(&self, req: Request) -> Result<JsonLines<T>> {
+        Ok(JsonLines::new(self.query_reader(req)?, response::decode::<T>))
     }
 
 }
@@ -621,42 +1386,223 @@ impl IPFS {
 mod tests {
 
     use super::*;
-    use jsonify::Jsonify;
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::TcpListener;
+    use std::thread;
+
+    /// Answers one request on `stream` with `body`, handing back the request line.
+    pub(crate) fn answer<S: Read + Write>(stream: S, body: &str) -> String {
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        let mut header = String::new();
+        while reader.read_line(&mut header).unwrap() > 2 {
+            header.clear();
+        }
+        let reply = format!("HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                             Connection: close\r\n\r\n{}", body.len(), body);
+        reader.get_mut().write_all(reply.as_bytes()).unwrap();
+        request_line.trim_end().to_string()
+    }
+
+    #[test]
+    fn calls_without_a_body_are_posted() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let ipfs = IPFS::connect(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let server = thread::spawn(move || {
+            listener.incoming().take(3).map(|stream| answer(stream.unwrap(), "")).collect::<Vec<_>>()
+        });
+        ipfs.shutdown().unwrap();
+        let requests = server.join().unwrap();
+        assert_eq!("POST /api/v0/shutdown HTTP/1.1", requests[2]);
+        assert!(requests.iter().all(|r| r.starts_with("POST ")), "{:?}", requests);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn unix_socket_multiaddrs_are_dialled() {
+        use std::os::unix::net::UnixListener;
+        let socket = std::env::temp_dir().join(format!("ipfs-api-{}.sock", std::process::id()));
+        let listener = UnixListener::bind(&socket).unwrap();
+        let ipfs = IPFS::connect(&format!("/unix{}", socket.display())).unwrap();
+        let server = thread::spawn(move || {
+            let bodies = ["", "", "hello"];
+            bodies.iter().zip(listener.incoming()).map(|(body, stream)| answer(stream.unwrap(), body)).collect::<Vec<_>>()
+        });
+        let content = ipfs.cat("QmHello");
+        let requests = server.join().unwrap();
+        std::fs::remove_file(&socket).unwrap();
+        assert_eq!(b"hello", &content.unwrap()[..]);
+        assert!(requests[2].starts_with("POST /api/v0/cat?arg=QmHello "), "{:?}", requests);
+    }
+
+    #[test]
+    fn failed_capabilities_are_fetched_once() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let ipfs = IPFS::connect(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let server = thread::spawn(move || {
+            listener.incoming().take(4).map(|stream| answer(stream.unwrap(), "")).collect::<Vec<_>>()
+        });
+        ipfs.shutdown().unwrap();
+        ipfs.shutdown().unwrap();
+        let requests = server.join().unwrap();
+        assert!(requests[1].starts_with("POST /api/v0/commands"), "{:?}", requests);
+        assert!(requests[3].starts_with("POST /api/v0/shutdown"), "{:?}", requests);
+        // The daemon is gone now, and asking says so.
+        assert!(ipfs.capabilities().is_err());
+    }
+
+    #[test]
+    fn capabilities_are_fetched_again_once_the_daemon_is_up() {
+        let addr = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap();
+        let ipfs = IPFS::connect(&format!("http://{}", addr)).unwrap();
+        assert!(ipfs.shutdown().is_err());
+        let listener = TcpListener::bind(addr).unwrap();
+        let server = thread::spawn(move || {
+            let bodies = [
+                r#"{"Version":"0.10.0","Commit":"","Repo":"11","System":"amd64/linux","Golang":"go1.16"}"#,
+                r#"{"Name":"ipfs","Subcommands":[{"Name":"shutdown"}]}"#,
+                "",
+            ];
+            bodies.iter().zip(listener.incoming()).map(|(body, stream)| answer(stream.unwrap(), body)).collect::<Vec<_>>()
+        });
+        ipfs.shutdown().unwrap();
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /api/v0/version"), "{:?}", requests);
+        assert_eq!("0.10.0", ipfs.capabilities().unwrap().version.version);
+        assert_eq!(Some(PubsubEncoding::Base64), ipfs.pubsub_encoding());
+    }
 
     #[test]
     fn cat_returns_correct_value() {
         let mut ipfs = IPFS::new();
         ipfs.host("http://localhost", 5001);
-        let ipfs_response = ipfs.cat("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH");
-        let parsed_response = Jsonify::decode_to_str(ipfs_response);
-        assert_eq!("hello, it really works!\n", parsed_response);
+        let ipfs_response = ipfs.cat("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH").unwrap();
+        assert_eq!(b"hello, it really works!\n".to_vec(), ipfs_response);
+    }
+
+    #[test]
+    fn cat_reader_streams_value() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        let mut reader = ipfs.cat_reader("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH").unwrap();
+        let mut content = String::new();
+        reader.read_to_string(&mut content).unwrap();
+        assert_eq!("hello, it really works!\n", content);
+    }
+
+    #[test]
+    fn cat_to_writes_value() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        let mut content = Vec::new();
+        let written = ipfs.cat_to("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH", &mut content).unwrap();
+        assert_eq!(24, written);
+        assert_eq!(b"hello, it really works!\n".to_vec(), content);
     }
 
     #[test]
     fn add_returns_correct_hash() {
         let mut ipfs = IPFS::new();
         ipfs.host("http://localhost", 5001);
-        let ipfs_response = ipfs.add("./it_works.txt");
-        let parsed_response = Jsonify::to_json2(&ipfs_response);
-        let hashsumm = &parsed_response["Hash"].to_string();
-        assert_eq!("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH", hashsumm);
+        let ipfs_response = ipfs.add("./it_works.txt").unwrap();
+        assert_eq!("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH", ipfs_response.hash);
+    }
+
+    #[test]
+    fn version_matches_capabilities() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        let ipfs_response = ipfs.version(&Version::new()).unwrap();
+        assert!(!ipfs_response.version.is_empty());
+        assert_eq!(ipfs.capabilities().unwrap().version, ipfs_response);
     }
 
     #[test]
-    fn version_returns_correct_ver() {
+    fn pubsub_ls_lists_topics_or_explains() {
         let mut ipfs = IPFS::new();
         ipfs.host("http://localhost", 5001);
-        let ipfs_response = ipfs.version(false, false, false, false);
-        let parsed_response = Jsonify::decode_to_str(ipfs_response);
-        assert_eq!("{\"Version\":\"0.4.10\",\"Commit\":\"4679f80\",\"Repo\":\"5\",\"System\":\"amd64/darwin\",\"Golang\":\"go1.8.3\"}\n", parsed_response);
+        match ipfs.pubsub_ls() {
+            Ok(_) | Err(IpfsError::Unsupported { .. }) => {}
+            // Daemons without pubsub enabled say how to turn it on.
+            Err(IpfsError::Api { message, .. }) => assert!(message.contains("pubsub"), "{}", message),
+            other => panic!("expected topics or an explanation, got {:?}", other),
+        }
     }
 
     #[test]
-    fn pubsub_ls_returns_warning() {
+    fn unsupported_commands_are_not_sent() {
         let mut ipfs = IPFS::new();
         ipfs.host("http://localhost", 5001);
-        let ipfs_response = ipfs.pubsub_ls();
-        let parsed_response = Jsonify::decode_to_str(ipfs_response);
-        assert_eq!("{\"Message\":\"experimental pubsub feature not enabled. Run daemon with --enable-pubsub-experiment to use.\",\"Code\":0}\n", parsed_response);
+        let caps = ipfs.capabilities().unwrap();
+        if caps.supports("tour/list") {
+            return;
+        }
+        match ipfs.tour_list() {
+            Err(IpfsError::Unsupported { command, version }) => {
+                assert_eq!("tour/list", command);
+                assert_eq!(caps.version.version, version);
+            }
+            other => panic!("expected an unsupported error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn add_bytes_returns_correct_hash() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        let ipfs_response = ipfs.add(Upload::bytes("it_works.txt", "hello, it really works!\n")).unwrap();
+        assert_eq!("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH", ipfs_response.hash);
+    }
+
+    #[test]
+    fn add_progress_ends_with_added_entry() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        let events = ipfs.add_progress("./it_works.txt", &AddOptions::new()).unwrap();
+        let last = events.map(|e| e.unwrap()).last().unwrap();
+        match last {
+            AddEvent::Added(added) => assert_eq!("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH", added.hash),
+            other => panic!("expected the added entry, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn client_is_shared_between_threads() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        let threads: Vec<_> = (0..4).map(|_| {
+            let ipfs = ipfs.clone();
+            std::thread::spawn(move || ipfs.cat("QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH").unwrap())
+        }).collect();
+        for thread in threads {
+            assert_eq!(b"hello, it really works!\n".to_vec(), thread.join().unwrap());
+        }
+    }
+
+    #[test]
+    fn add_missing_file_is_io_error() {
+        let mut ipfs = IPFS::new();
+        ipfs.host("http://localhost", 5001);
+        match ipfs.add("./does_not_exist.txt") {
+            Err(IpfsError::Io(_)) => {}
+            other => panic!("expected an i/o error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn error_body_is_decoded() {
+        let body = b"{\"Message\":\"invalid path\",\"Code\":0,\"Type\":\"error\"}\n";
+        match IpfsError::from_response(500, body) {
+            IpfsError::Api { message, code } => {
+                assert_eq!("invalid path", message);
+                assert_eq!(0, code);
+            }
+            other => panic!("expected an api error, got {:?}", other),
+        }
+        match IpfsError::from_response(404, b"404 page not found") {
+            IpfsError::Status(404) => {}
+            other => panic!("expected a status error, got {:?}", other),
+        }
     }
 }
@@ -0,0 +1,315 @@
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+use crate::api;
+use crate::error::{IpfsError, Result};
+use crate::files::FilesWriteOptions;
+use crate::multipart::Upload;
+use crate::params::{FilesLs, FilesMkdir, FilesRead, FilesRm, FilesStat};
+use crate::response::{self, FilesEntry, FilesStatResponse};
+use crate::IPFS;
+
+/// The daemon's mutable file system, seen through `std::fs`-like calls.
+///
+/// Paths are MFS paths such as `/docs/a.txt`. Get one from `IPFS::mfs`:
+///
+/// ```no_run
+/// # use ipfs_api::IPFS;
+/// # use std::io::{Read, Seek, SeekFrom};
+/// let mfs = IPFS::new().mfs();
+/// mfs.create_dir_all("/docs/notes").unwrap();
+/// mfs.write("/docs/notes/a.txt", &b"hello, mfs"[..]).unwrap();
+/// for entry in mfs.read_dir("/docs/notes").unwrap() {
+///     println!("{} {}", entry.name, entry.size);
+/// }
+/// let mut file = mfs.open("/docs/notes/a.txt").unwrap();
+/// file.seek(SeekFrom::Start(7)).unwrap();
+/// let mut rest = String::new();
+/// file.read_to_string(&mut rest).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Mfs {
+    ipfs: IPFS,
+}
+
+/// What an MFS path points at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MfsFileType {
+    File,
+    Directory,
+}
+
+/// A file or directory in MFS, as listed by `Mfs::read_dir` or looked up by `Mfs::metadata`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MfsEntry {
+    pub name: String,
+    pub file_type: MfsFileType,
+    pub size: u64,
+    pub hash: String,
+}
+
+impl MfsEntry {
+    pub fn is_dir(&self) -> bool {
+        self.file_type == MfsFileType::Directory
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.file_type == MfsFileType::File
+    }
+
+    fn from_stat(path: &str, stat: FilesStatResponse) -> MfsEntry {
+        MfsEntry {
+            name: path.trim_end_matches('/').rsplit('/').next().unwrap_or_default().to_string(),
+            file_type: if stat.file_type == "directory" { MfsFileType::Directory } else { MfsFileType::File },
+            size: stat.size,
+            hash: stat.hash,
+        }
+    }
+}
+
+impl From<FilesEntry> for MfsEntry {
+    fn from(entry: FilesEntry) -> MfsEntry {
+        MfsEntry {
+            name: entry.name,
+            // `files/ls` reports 1 for directories and 0 for files.
+            file_type: if entry.entry_type == 1 { MfsFileType::Directory } else { MfsFileType::File },
+            size: entry.size,
+            hash: entry.hash,
+        }
+    }
+}
+
+impl IPFS {
+    /// A handle on the daemon's mutable file system, sharing this client's connections.
+    pub fn mfs(&self) -> Mfs {
+        Mfs { ipfs: self.clone() }
+    }
+}
+
+impl Mfs {
+    pub fn read_dir(&self, path: &str) -> Result<Vec<MfsEntry>> {
+        let r = self.ipfs.query(api::files_ls(FilesLs::new(path).long(true)))?;
+        let entries: Vec<FilesEntry> = response::decode_list(&r, "Entries")?;
+        Ok(entries.into_iter().map(MfsEntry::from).collect())
+    }
+
+    pub fn metadata(&self, path: &str) -> Result<MfsEntry> {
+        let r = self.ipfs.query(api::files_stat(&FilesStat::new(path)))?;
+        Ok(MfsEntry::from_stat(path, response::decode(&r)?))
+    }
+
+    /// Creates the directory `path`, which fails if its parent is missing.
+    pub fn create_dir(&self, path: &str) -> Result<()> {
+        self.ipfs.query(api::files_mkdir(&FilesMkdir::new(path))).map(|_| ())
+    }
+
+    /// Creates the directory `path` along with any missing parents.
+    pub fn create_dir_all(&self, path: &str) -> Result<()> {
+        self.ipfs.query(api::files_mkdir(FilesMkdir::new(path).parents(true))).map(|_| ())
+    }
+
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        self.ipfs.query(api::files_mv(from, to)).map(|_| ())
+    }
+
+    /// Removes the file or empty directory at `path`.
+    pub fn remove(&self, path: &str) -> Result<()> {
+        self.ipfs.query(api::files_rm(&FilesRm::new(path))).map(|_| ())
+    }
+
+    /// Removes `path` and, if it is a directory, everything below it.
+    pub fn remove_all(&self, path: &str) -> Result<()> {
+        self.ipfs.query(api::files_rm(FilesRm::new(path).recursive(true))).map(|_| ())
+    }
+
+    /// Copies `from` to `to`. `from` may also be an `/ipfs/...` path, which links existing
+    /// content into MFS without fetching it.
+    pub fn copy(&self, from: &str, to: &str) -> Result<()> {
+        self.ipfs.query(api::files_cp(from, to)).map(|_| ())
+    }
+
+    /// Replaces the content of the file at `path`, creating it if needed.
+    pub fn write<U: Into<Upload>>(&self, path: &str, data: U) -> Result<()> {
+        let req = api::files_write(path, data.into(), FilesWriteOptions::new().create(true).truncate(true));
+        self.ipfs.query(req).map(|_| ())
+    }
+
+    pub fn read_to_end(&self, path: &str) -> Result<Vec<u8>> {
+        self.ipfs.query(api::files_read(&FilesRead::new(path)))
+    }
+
+    /// Writes the changes under `path` out to the blockstore; `metadata` then has its new hash.
+    pub fn flush(&self, path: &str) -> Result<()> {
+        self.ipfs.query(api::files_flush(path)).map(|_| ())
+    }
+
+    /// Changes the CID version or hash function of `path` and the nodes below it.
+    pub fn chcid(&self, path: &str, cid_version: Option<u32>, hash: Option<&str>) -> Result<()> {
+        self.ipfs.query(api::files_chcid(path, cid_version, hash)).map(|_| ())
+    }
+
+    /// Opens the existing file at `path` for reading and writing, positioned at its start.
+    pub fn open(&self, path: &str) -> Result<MfsFile> {
+        let entry = self.metadata(path)?;
+        if entry.is_dir() {
+            return Err(IpfsError::Io(io::Error::new(io::ErrorKind::InvalidInput,
+                                                    format!("{} is a directory", path))));
+        }
+        Ok(MfsFile { ipfs: self.ipfs.clone(), path: path.to_string(), pos: 0, size: entry.size })
+    }
+
+    /// Opens the file at `path` for reading and writing, creating it or emptying it first.
+    pub fn create(&self, path: &str) -> Result<MfsFile> {
+        self.write(path, Vec::new())?;
+        Ok(MfsFile { ipfs: self.ipfs.clone(), path: path.to_string(), pos: 0, size: 0 })
+    }
+}
+
+/// An MFS file opened by `Mfs::open` or `Mfs::create`.
+///
+/// Every `read` and `write` is a call to the daemon at the current offset, so wrap it in a
+/// `BufReader` or `BufWriter` when moving data in small pieces.
+///
+/// Nothing locks the file against other writers. Reads see what they wrote, but whether the file
+/// has grown or shrunk is only asked when a read reaches the end it knew of, which costs another
+/// call, and a read racing with another writer gets whichever content the daemon had at the time.
+pub struct MfsFile {
+    ipfs: IPFS,
+    path: String,
+    pos: u64,
+    size: u64,
+}
+
+impl MfsFile {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Asks the daemon for the file's current size.
+    fn refresh_size(&mut self) -> io::Result<u64> {
+        let req = api::files_stat(&FilesStat::new(&self.path));
+        let stat: FilesStatResponse = self.ipfs.query(req)
+            .and_then(|r| response::decode(&r))
+            .map_err(io::Error::other)?;
+        self.size = stat.size;
+        Ok(stat.size)
+    }
+}
+
+impl Read for MfsFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        // The daemon refuses offsets past the end rather than reading nothing, but another writer
+        // may have grown the file since.
+        if self.pos >= self.size && self.pos >= self.refresh_size()? {
+            return Ok(0);
+        }
+        let req = api::files_read(FilesRead::new(&self.path).offset(self.pos as i64).count(buf.len() as i64));
+        let data = match self.ipfs.query(req) {
+            Ok(data) => data,
+            // It may have shrunk instead, leaving this offset past the end.
+            Err(_) if self.pos >= self.refresh_size()? => return Ok(0),
+            Err(e) => return Err(io::Error::other(e)),
+        };
+        let n = data.len().min(buf.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        self.pos += n as u64;
+        Ok(n)
+    }
+}
+
+impl Write for MfsFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let mut options = FilesWriteOptions::new();
+        options.create(true).offset(self.pos);
+        let req = api::files_write(&self.path, Upload::from(buf), &options);
+        self.ipfs.query(req).map_err(io::Error::other)?;
+        self.pos += buf.len() as u64;
+        self.size = self.size.max(self.pos);
+        Ok(buf.len())
+    }
+
+    /// Writes are sent as they are made, so there is nothing left to flush.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Seek for MfsFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let (base, offset) = match pos {
+            SeekFrom::Start(n) => {
+                self.pos = n;
+                return Ok(n);
+            }
+            SeekFrom::Current(n) => (self.pos, n),
+            SeekFrom::End(n) => (self.refresh_size()?, n),
+        };
+        self.pos = base.checked_add_signed(offset).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "invalid seek to a negative or overflowing position")
+        })?;
+        Ok(self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::tests::answer;
+    use std::net::TcpListener;
+    use std::thread;
+
+    #[test]
+    fn entries_are_typed() {
+        let dir = MfsEntry::from(FilesEntry {
+            name: "docs".to_string(),
+            entry_type: 1,
+            size: 0,
+            hash: "QmDir".to_string(),
+        });
+        assert!(dir.is_dir());
+        let stat = FilesStatResponse {
+            hash: "QmFile".to_string(),
+            size: 5,
+            cumulative_size: 13,
+            blocks: 0,
+            file_type: "file".to_string(),
+        };
+        let file = MfsEntry::from_stat("/docs/a.txt", stat);
+        assert!(file.is_file());
+        assert_eq!("a.txt", file.name);
+    }
+
+    #[test]
+    fn seeking_before_the_start_fails() {
+        let mut file = MfsFile { ipfs: IPFS::new(), path: "/a.txt".to_string(), pos: 0, size: 5 };
+        assert_eq!(4, file.seek(SeekFrom::Start(4)).unwrap());
+        assert_eq!(6, file.seek(SeekFrom::Current(2)).unwrap());
+        assert!(file.seek(SeekFrom::Current(-7)).is_err());
+        assert_eq!(6, file.stream_position().unwrap());
+    }
+
+    #[test]
+    fn reading_at_the_end_asks_whether_the_file_grew() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let ipfs = IPFS::connect(&format!("http://{}", listener.local_addr().unwrap())).unwrap();
+        let stat = r#"{"Hash":"QmFile","Size":8,"CumulativeSize":19,"Blocks":0,"Type":"file"}"#;
+        let server = thread::spawn(move || {
+            // Capabilities come first; empty replies leave them unknown.
+            let bodies = ["", "", stat, "abc", stat];
+            bodies.iter().zip(listener.incoming()).map(|(body, stream)| answer(stream.unwrap(), body)).collect::<Vec<_>>()
+        });
+        let mut file = MfsFile { ipfs, path: "/a.txt".to_string(), pos: 5, size: 5 };
+        let mut rest = Vec::new();
+        file.read_to_end(&mut rest).unwrap();
+        let requests = server.join().unwrap();
+        assert_eq!(b"abc", &rest[..]);
+        assert!(requests[3].starts_with("POST /api/v0/files/read?arg=%2Fa.txt&offset=5&"), "{:?}", requests);
+        assert_eq!(0, file.read(&mut []).unwrap());
+    }
+}
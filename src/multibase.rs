@@ -0,0 +1,119 @@
+use rustc_serialize::base64::{self, FromBase64, ToBase64};
+use rustc_serialize::hex::FromHex;
+
+const BASE32: &[u8] = b"abcdefghijklmnopqrstuvwxyz234567";
+const BASE58: &[u8] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Decodes a multibase string: its first character names the base of the rest.
+///
+/// Covers what the daemon sends: the base64 family, hex, base32 and base58btc.
+pub(crate) fn decode(inp: &str) -> Option<Vec<u8>> {
+    let mut chars = inp.chars();
+    let prefix = chars.next()?;
+    let rest = chars.as_str();
+    match prefix {
+        'm' | 'M' | 'u' | 'U' => rest.from_base64().ok(),
+        'f' | 'F' => rest.from_hex().ok(),
+        'b' | 'B' => base32_decode(&rest.to_ascii_lowercase()),
+        'z' => base58_decode(rest),
+        _ => None,
+    }
+}
+
+/// Encodes `data` as unpadded base64url, the multibase the daemon expects for topic names.
+pub(crate) fn encode(data: &[u8]) -> String {
+    let config = base64::Config {
+        char_set: base64::CharacterSet::UrlSafe,
+        newline: base64::Newline::LF,
+        pad: false,
+        line_length: None,
+    };
+    format!("u{}", data.to_base64(config))
+}
+
+fn base32_decode(inp: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(inp.len() * 5 / 8);
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in inp.trim_end_matches('=').bytes() {
+        let value = BASE32.iter().position(|&b| b == c)? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+fn base58_decode(inp: &str) -> Option<Vec<u8>> {
+    let mut out: Vec<u8> = Vec::new();
+    for c in inp.bytes() {
+        let mut carry = BASE58.iter().position(|&b| b == c)? as u32;
+        for byte in out.iter_mut().rev() {
+            carry += (*byte as u32) * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            out.insert(0, carry as u8);
+            carry >>= 8;
+        }
+    }
+    let zeros = inp.bytes().take_while(|&c| c == b'1').count();
+    let mut decoded = vec![0; zeros];
+    decoded.extend(out);
+    Some(decoded)
+}
+
+/// Encodes `data` as plain base58btc, the way peer IDs are written.
+pub(crate) fn base58_encode(data: &[u8]) -> String {
+    let mut digits: Vec<u8> = Vec::new();
+    for &byte in data {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+    let zeros = data.iter().take_while(|&&b| b == 0).count();
+    let mut encoded = "1".repeat(zeros);
+    encoded.extend(digits.iter().rev().map(|&d| BASE58[d as usize] as char));
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn decodes_every_supported_base() {
+        let hello = b"hello".to_vec();
+        assert_eq!(Some(hello.clone()), decode("maGVsbG8"));
+        assert_eq!(Some(hello.clone()), decode("uaGVsbG8"));
+        assert_eq!(Some(hello.clone()), decode("f68656c6c6f"));
+        assert_eq!(Some(hello.clone()), decode("bnbswy3dp"));
+        assert_eq!(Some(hello), decode("zCn8eVZg"));
+        assert_eq!(None, decode("?what"));
+    }
+
+    #[test]
+    fn encodes_base64url() {
+        assert_eq!("uaGk_", encode(b"hi?"));
+        assert_eq!(Some(b"hi?".to_vec()), decode(&encode(b"hi?")));
+    }
+
+    #[test]
+    fn base58_round_trips() {
+        let data = [0u8, 0, 0x12, 0x20, 0xff, 0x01];
+        assert_eq!(Some(data.to_vec()), base58_decode(&base58_encode(&data)));
+        assert_eq!("Cn8eVZg", base58_encode(b"hello"));
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::query::percent_encode;
+
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+enum Source {
+    Path(PathBuf),
+    Bytes(Vec<u8>),
+    Reader(Box<dyn Read + Send>),
+    Directory,
+    Symlink(PathBuf),
+}
+
+/// Content sent to the daemon by the uploading calls (`add`, `block_put`, `dag_put`, ...).
+///
+/// A `&str`, `&Path` or `PathBuf` uploads the file at that path, while `&[u8]` and `Vec<u8>`
+/// upload the bytes themselves, so every uploading call takes any of them directly:
+///
+/// ```no_run
+/// # use ipfs_api::{IPFS, Upload};
+/// # let ipfs = IPFS::new();
+/// ipfs.add("./it_works.txt").unwrap();
+/// ipfs.add(&b"hello, it really works!\n"[..]).unwrap();
+/// ipfs.add(Upload::reader("stdin.txt", std::io::stdin())).unwrap();
+/// ```
+pub struct Upload {
+    name: String,
+    source: Source,
+}
+
+impl Upload {
+    /// Uploads the file at `path`, named after its last component.
+    pub fn path<P: AsRef<Path>>(path: P) -> Upload {
+        let path = path.as_ref();
+        Upload {
+            name: path.file_name().map(|f| f.to_string_lossy().into_owned()).unwrap_or_default(),
+            source: Source::Path(path.to_path_buf()),
+        }
+    }
+
+    /// Uploads `data` under the file name `name`.
+    pub fn bytes<N: Into<String>, D: Into<Vec<u8>>>(name: N, data: D) -> Upload {
+        Upload { name: name.into(), source: Source::Bytes(data.into()) }
+    }
+
+    /// Uploads everything `reader` yields under the file name `name`, without buffering it.
+    pub fn reader<N: Into<String>, R: Read + Send + 'static>(name: N, reader: R) -> Upload {
+        Upload { name: name.into(), source: Source::Reader(Box::new(reader)) }
+    }
+
+    /// An empty directory entry, used when walking a tree for `add_path`.
+    pub(crate) fn directory<N: Into<String>>(name: N) -> Upload {
+        Upload { name: name.into(), source: Source::Directory }
+    }
+
+    /// A symlink entry pointing at `target`, used when walking a tree for `add_path`.
+    pub(crate) fn symlink<N: Into<String>>(name: N, target: PathBuf) -> Upload {
+        Upload { name: name.into(), source: Source::Symlink(target) }
+    }
+
+    /// The same content under the file name `name`.
+    pub(crate) fn named<N: Into<String>>(mut self, name: N) -> Upload {
+        self.name = name.into();
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self.source {
+            Source::Directory => "application/x-directory",
+            Source::Symlink(_) => "application/symlink",
+            _ => "application/octet-stream",
+        }
+    }
+}
+
+impl fmt::Debug for Upload {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let source = match self.source {
+            Source::Path(ref p) => format!("{:?}", p),
+            Source::Bytes(ref b) => format!("{} bytes", b.len()),
+            Source::Reader(_) => "reader".to_string(),
+            Source::Directory => "directory".to_string(),
+            Source::Symlink(ref t) => format!("symlink to {:?}", t),
+        };
+        f.debug_struct("Upload").field("name", &self.name).field("source", &source).finish()
+    }
+}
+
+impl<'a> From<&'a str> for Upload {
+    fn from(path: &'a str) -> Upload {
+        Upload::path(path)
+    }
+}
+
+impl<'a> From<&'a Path> for Upload {
+    fn from(path: &'a Path) -> Upload {
+        Upload::path(path)
+    }
+}
+
+impl From<PathBuf> for Upload {
+    fn from(path: PathBuf) -> Upload {
+        Upload::path(path)
+    }
+}
+
+impl<'a> From<&'a [u8]> for Upload {
+    fn from(data: &'a [u8]) -> Upload {
+        Upload::bytes("", data)
+    }
+}
+
+impl From<Vec<u8>> for Upload {
+    fn from(data: Vec<u8>) -> Upload {
+        Upload::bytes("", data)
+    }
+}
+
+/// A `multipart/form-data` body whose parts are streamed rather than loaded up front.
+pub(crate) struct Multipart {
+    boundary: String,
+    parts: Vec<(String, Upload)>,
+}
+
+impl Multipart {
+    pub fn new() -> Multipart {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.subsec_nanos()).unwrap_or(0);
+        let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+        Multipart {
+            boundary: format!("------------------------ipfs{:08x}{:08x}", nanos, count),
+            parts: Vec::new(),
+        }
+    }
+
+    /// Adds `upload` as a part named `name`.
+    pub fn add(mut self, name: &str, upload: Upload) -> Multipart {
+        self.parts.push((name.to_string(), upload));
+        self
+    }
+
+    pub fn content_type(&self) -> String {
+        format!("multipart/form-data; boundary={}", self.boundary)
+    }
+
+    /// Turns the form into the body bytes.
+    ///
+    /// Files are only opened once the reader gets to them, but they must exist already.
+    pub fn into_reader(self) -> io::Result<MultipartReader> {
+        let mut segments = VecDeque::new();
+        for (name, upload) in self.parts {
+            if let Source::Path(ref path) = upload.source {
+                fs::metadata(path)?;
+            }
+            let header = format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"; filename=\"{}\"\r\n\
+                 Content-Type: {}\r\n\r\n",
+                self.boundary, name, escape_filename(&upload.name), upload.content_type());
+            segments.push_back(Segment::Ready(Box::new(Cursor::new(header.into_bytes()))));
+            segments.push_back(match upload.source {
+                Source::Path(path) => Segment::Path(path),
+                Source::Bytes(data) => Segment::Ready(Box::new(Cursor::new(data))),
+                Source::Reader(reader) => Segment::Ready(reader),
+                Source::Directory => Segment::Ready(Box::new(io::empty())),
+                Source::Symlink(target) => {
+                    let target = target.to_string_lossy().into_owned();
+                    Segment::Ready(Box::new(Cursor::new(target.into_bytes())))
+                }
+            });
+            segments.push_back(Segment::Ready(Box::new(Cursor::new(b"\r\n".to_vec()))));
+        }
+        let closing = format!("--{}--\r\n", self.boundary);
+        segments.push_back(Segment::Ready(Box::new(Cursor::new(closing.into_bytes()))));
+        Ok(MultipartReader { segments })
+    }
+}
+
+/// The daemon url-unescapes file names, so anything beyond unreserved characters and the
+/// path separator has to be escaped.
+fn escape_filename(name: &str) -> String {
+    percent_encode(name, b"/")
+}
+
+enum Segment {
+    Path(PathBuf),
+    Ready(Box<dyn Read + Send>),
+}
+
+/// The encoded body of a `Multipart`.
+pub(crate) struct MultipartReader {
+    segments: VecDeque<Segment>,
+}
+
+impl Read for MultipartReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = match self.segments.front_mut() {
+                None => return Ok(0),
+                Some(&mut Segment::Path(ref path)) => {
+                    let file = File::open(path)?;
+                    self.segments[0] = Segment::Ready(Box::new(file));
+                    continue;
+                }
+                Some(&mut Segment::Ready(ref mut reader)) => reader.read(buf)?,
+            };
+            if n > 0 || buf.is_empty() {
+                return Ok(n);
+            }
+            self.segments.pop_front();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn encode(form: Multipart) -> String {
+        let boundary = form.boundary.clone();
+        let mut body = String::new();
+        form.into_reader().unwrap().read_to_string(&mut body).unwrap();
+        body.replace(&boundary, "BOUNDARY")
+    }
+
+    #[test]
+    fn parts_are_framed_by_boundaries() {
+        let form = Multipart::new()
+            .add("arg", Upload::bytes("a.txt", "hello"))
+            .add("arg", Upload::reader("b.txt", Cursor::new(b"world".to_vec())));
+        assert_eq!("--BOUNDARY\r\nContent-Disposition: form-data; name=\"arg\"; filename=\"a.txt\"\r\n\
+                    Content-Type: application/octet-stream\r\n\r\nhello\r\n\
+                    --BOUNDARY\r\nContent-Disposition: form-data; name=\"arg\"; filename=\"b.txt\"\r\n\
+                    Content-Type: application/octet-stream\r\n\r\nworld\r\n\
+                    --BOUNDARY--\r\n", encode(form));
+    }
+
+    #[test]
+    fn paths_are_named_after_the_file() {
+        let form = Multipart::new().add("arg", Upload::from("./it_works.txt"));
+        assert!(encode(form).contains("filename=\"it_works.txt\"\r\n"));
+    }
+
+    #[test]
+    fn directories_and_odd_names() {
+        let form = Multipart::new()
+            .add("arg", Upload::directory("my docs"))
+            .add("arg", Upload::bytes("my docs/a+b.txt", "x"));
+        let body = encode(form);
+        assert!(body.contains("filename=\"my%20docs\"\r\nContent-Type: application/x-directory\r\n\r\n\r\n"));
+        assert!(body.contains("filename=\"my%20docs/a%2Bb.txt\"\r\n"));
+    }
+
+    #[test]
+    fn missing_files_fail_up_front() {
+        let form = Multipart::new().add("arg", Upload::from("./does_not_exist.txt"));
+        assert!(form.into_reader().is_err());
+    }
+}
@@ -0,0 +1,1436 @@
+//! Requests for the calls whose parameters are mostly optional.
+//!
+//! Each one starts from its required arguments and only sends what is set afterwards, so
+//! everything else stays at the daemon's default:
+//!
+//! ```no_run
+//! # use ipfs_api::{IPFS, NamePublish};
+//! # let ipfs = IPFS::new();
+//! let path = "/ipfs/QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH";
+//! ipfs.name_publish(NamePublish::new(path).lifetime("24h").key("mykey")).unwrap();
+//! ```
+
+use crate::query::Query;
+
+/// Parameters of `block_put`.
+#[derive(Clone, Debug, Default)]
+pub struct BlockPut {
+    format: Option<String>,
+    mhtype: Option<String>,
+    mhlen: Option<i64>,
+    pin: Option<bool>,
+}
+
+impl BlockPut {
+
+    pub fn new() -> BlockPut {
+        Default::default()
+    }
+
+    /// The codec of the block, e.g. `raw` or `dag-pb`.
+    pub fn format(&mut self, inp: &str) -> &mut BlockPut {
+        self.format = Some(inp.to_string());
+        self
+    }
+
+    /// The multihash function, e.g. `sha2-256`.
+    pub fn mhtype(&mut self, inp: &str) -> &mut BlockPut {
+        self.mhtype = Some(inp.to_string());
+        self
+    }
+
+    /// The length the multihash is truncated to.
+    pub fn mhlen(&mut self, inp: i64) -> &mut BlockPut {
+        self.mhlen = Some(inp);
+        self
+    }
+
+    /// Pins the block once it is stored.
+    pub fn pin(&mut self, inp: bool) -> &mut BlockPut {
+        self.pin = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .opt("format", self.format.as_ref())
+            .opt("mhtype", self.mhtype.as_ref())
+            .opt("mhlen", self.mhlen)
+            .opt("pin", self.pin)
+    }
+}
+
+/// Parameters of `block_rm`.
+#[derive(Clone, Debug)]
+pub struct BlockRm {
+    hash: String,
+    force: Option<bool>,
+    quiet: Option<bool>,
+}
+
+impl BlockRm {
+
+    pub fn new(hash: &str) -> BlockRm {
+        BlockRm { hash: hash.to_string(), force: None, quiet: None }
+    }
+
+    /// Ignores blocks that do not exist.
+    pub fn force(&mut self, inp: bool) -> &mut BlockRm {
+        self.force = Some(inp);
+        self
+    }
+
+    /// Only reports blocks that could not be removed.
+    pub fn quiet(&mut self, inp: bool) -> &mut BlockRm {
+        self.quiet = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.hash).opt("force", self.force).opt("quiet", self.quiet)
+    }
+}
+
+/// Parameters of `cid_format`.
+#[derive(Clone, Debug)]
+pub struct CidFormat {
+    cid: String,
+    format: Option<String>,
+    version: Option<u32>,
+    codec: Option<String>,
+    base: Option<String>,
+}
+
+impl CidFormat {
+
+    pub fn new(cid: &str) -> CidFormat {
+        CidFormat { cid: cid.to_string(), format: None, version: None, codec: None, base: None }
+    }
+
+    /// A `printf`-style template for the output, e.g. `%b-%v-%c-%h-%L`.
+    pub fn format(&mut self, inp: &str) -> &mut CidFormat {
+        self.format = Some(inp.to_string());
+        self
+    }
+
+    /// Converts the CID to this version.
+    pub fn version(&mut self, inp: u32) -> &mut CidFormat {
+        self.version = Some(inp);
+        self
+    }
+
+    /// Replaces the content codec, e.g. `raw` or `dag-pb`.
+    pub fn codec(&mut self, inp: &str) -> &mut CidFormat {
+        self.codec = Some(inp.to_string());
+        self
+    }
+
+    /// The multibase to write the CID in, e.g. `base32` or `base58btc`.
+    pub fn base(&mut self, inp: &str) -> &mut CidFormat {
+        self.base = Some(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .arg(&self.cid)
+            .opt("f", self.format.as_ref())
+            .opt("v", self.version)
+            .opt("mc", self.codec.as_ref())
+            .opt("b", self.base.as_ref())
+    }
+}
+
+/// Parameters of `dag_import`.
+#[derive(Clone, Debug, Default)]
+pub struct DagImport {
+    pin_roots: Option<bool>,
+}
+
+impl DagImport {
+
+    pub fn new() -> DagImport {
+        Default::default()
+    }
+
+    /// Pins the roots of the CAR file once it is imported. The daemon pins them by default.
+    pub fn pin_roots(&mut self, inp: bool) -> &mut DagImport {
+        self.pin_roots = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("pin-roots", self.pin_roots)
+    }
+}
+
+/// Parameters of `dag_put`.
+#[derive(Clone, Debug, Default)]
+pub struct DagPut {
+    format: Option<String>,
+    input_enc: Option<String>,
+    hash: Option<String>,
+    pin: Option<bool>,
+}
+
+impl DagPut {
+
+    pub fn new() -> DagPut {
+        Default::default()
+    }
+
+    /// The codec the node is stored with, e.g. `dag-cbor`.
+    pub fn format(&mut self, inp: &str) -> &mut DagPut {
+        self.format = Some(inp.to_string());
+        self
+    }
+
+    /// The encoding of the uploaded node, e.g. `json`.
+    pub fn input_enc(&mut self, inp: &str) -> &mut DagPut {
+        self.input_enc = Some(inp.to_string());
+        self
+    }
+
+    /// The multihash function, e.g. `sha2-256`.
+    pub fn hash(&mut self, inp: &str) -> &mut DagPut {
+        self.hash = Some(inp.to_string());
+        self
+    }
+
+    /// Pins the node once it is stored.
+    pub fn pin(&mut self, inp: bool) -> &mut DagPut {
+        self.pin = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .opt("format", self.format.as_ref())
+            .opt("input-enc", self.input_enc.as_ref())
+            .opt("hash", self.hash.as_ref())
+            .opt("pin", self.pin)
+    }
+}
+
+/// Parameters of `dht_provide` and `routing_provide`.
+#[derive(Clone, Debug)]
+pub struct DhtProvide {
+    key: String,
+    verbose: Option<bool>,
+    recursive: Option<bool>,
+}
+
+impl DhtProvide {
+
+    pub fn new(key: &str) -> DhtProvide {
+        DhtProvide { key: key.to_string(), verbose: None, recursive: None }
+    }
+
+    /// Reports every step of the query, not just its results.
+    pub fn verbose(&mut self, inp: bool) -> &mut DhtProvide {
+        self.verbose = Some(inp);
+        self
+    }
+
+    /// Also announces every block below `key`.
+    pub fn recursive(&mut self, inp: bool) -> &mut DhtProvide {
+        self.recursive = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.key).opt("verbose", self.verbose).opt("recursive", self.recursive)
+    }
+}
+
+/// Parameters of `dht_put`.
+#[derive(Clone, Debug)]
+pub struct DhtPut {
+    key: String,
+    value: String,
+    verbose: Option<bool>,
+}
+
+impl DhtPut {
+
+    pub fn new(key: &str, value: &str) -> DhtPut {
+        DhtPut { key: key.to_string(), value: value.to_string(), verbose: None }
+    }
+
+    /// Reports every step of the query, not just its results.
+    pub fn verbose(&mut self, inp: bool) -> &mut DhtPut {
+        self.verbose = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.key).arg(&self.value).opt("verbose", self.verbose)
+    }
+}
+
+/// Parameters of `dht_findpeer`, `dht_findprovs`, `dht_get` and `dht_query`, which all look up
+/// a single key or peer.
+#[derive(Clone, Debug)]
+pub struct DhtQuery {
+    key: String,
+    verbose: Option<bool>,
+}
+
+impl DhtQuery {
+
+    pub fn new(key: &str) -> DhtQuery {
+        DhtQuery { key: key.to_string(), verbose: None }
+    }
+
+    /// Reports every step of the query, not just its results.
+    pub fn verbose(&mut self, inp: bool) -> &mut DhtQuery {
+        self.verbose = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.key).opt("verbose", self.verbose)
+    }
+}
+
+/// Parameters of `diag_net`.
+#[derive(Clone, Debug, Default)]
+pub struct DiagNet {
+    vis: Option<String>,
+}
+
+impl DiagNet {
+
+    pub fn new() -> DiagNet {
+        Default::default()
+    }
+
+    /// The output format, e.g. `text` or `d3`.
+    pub fn vis(&mut self, inp: &str) -> &mut DiagNet {
+        self.vis = Some(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("vis", self.vis.as_ref())
+    }
+}
+
+/// Parameters of `dns`.
+#[derive(Clone, Debug)]
+pub struct Dns {
+    domain: String,
+    recursive: Option<bool>,
+}
+
+impl Dns {
+
+    pub fn new(domain: &str) -> Dns {
+        Dns { domain: domain.to_string(), recursive: None }
+    }
+
+    /// Keeps resolving until the result is no longer a DNS link. The daemon does by default.
+    pub fn recursive(&mut self, inp: bool) -> &mut Dns {
+        self.recursive = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.domain).opt("recursive", self.recursive)
+    }
+}
+
+/// Parameters of `files_ls`.
+#[derive(Clone, Debug)]
+pub struct FilesLs {
+    path: String,
+    long: Option<bool>,
+}
+
+impl FilesLs {
+
+    pub fn new(path: &str) -> FilesLs {
+        FilesLs { path: path.to_string(), long: None }
+    }
+
+    /// Includes the type, size and hash of every entry.
+    pub fn long(&mut self, inp: bool) -> &mut FilesLs {
+        self.long = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("long", self.long)
+    }
+}
+
+/// Parameters of `files_mkdir`.
+#[derive(Clone, Debug)]
+pub struct FilesMkdir {
+    path: String,
+    parents: Option<bool>,
+}
+
+impl FilesMkdir {
+
+    pub fn new(path: &str) -> FilesMkdir {
+        FilesMkdir { path: path.to_string(), parents: None }
+    }
+
+    /// Creates missing parent directories, and succeeds if the directory already exists.
+    pub fn parents(&mut self, inp: bool) -> &mut FilesMkdir {
+        self.parents = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("parents", self.parents)
+    }
+}
+
+/// Parameters of `files_read`, `files_read_reader` and `files_read_to`. Without an offset or
+/// count the whole file is read.
+#[derive(Clone, Debug)]
+pub struct FilesRead {
+    path: String,
+    offset: Option<i64>,
+    count: Option<i64>,
+}
+
+impl FilesRead {
+
+    pub fn new(path: &str) -> FilesRead {
+        FilesRead { path: path.to_string(), offset: None, count: None }
+    }
+
+    /// Where to start reading, in bytes.
+    pub fn offset(&mut self, inp: i64) -> &mut FilesRead {
+        self.offset = Some(inp);
+        self
+    }
+
+    /// How many bytes to read at most.
+    pub fn count(&mut self, inp: i64) -> &mut FilesRead {
+        self.count = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("offset", self.offset).opt("count", self.count)
+    }
+}
+
+/// Parameters of `files_rm`.
+#[derive(Clone, Debug)]
+pub struct FilesRm {
+    path: String,
+    recursive: Option<bool>,
+}
+
+impl FilesRm {
+
+    pub fn new(path: &str) -> FilesRm {
+        FilesRm { path: path.to_string(), recursive: None }
+    }
+
+    /// Removes directories along with everything in them.
+    pub fn recursive(&mut self, inp: bool) -> &mut FilesRm {
+        self.recursive = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("recursive", self.recursive)
+    }
+}
+
+/// Parameters of `files_stat`.
+#[derive(Clone, Debug)]
+pub struct FilesStat {
+    path: String,
+    format: Option<String>,
+    hash: Option<bool>,
+    size: Option<bool>,
+}
+
+impl FilesStat {
+
+    pub fn new(path: &str) -> FilesStat {
+        FilesStat { path: path.to_string(), format: None, hash: None, size: None }
+    }
+
+    /// A template for the output, e.g. `<hash> <size>`.
+    pub fn format(&mut self, inp: &str) -> &mut FilesStat {
+        self.format = Some(inp.to_string());
+        self
+    }
+
+    /// Only reports the hash.
+    pub fn hash(&mut self, inp: bool) -> &mut FilesStat {
+        self.hash = Some(inp);
+        self
+    }
+
+    /// Only reports the size.
+    pub fn size(&mut self, inp: bool) -> &mut FilesStat {
+        self.size = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .arg(&self.path)
+            .opt("format", self.format.as_ref())
+            .opt("hash", self.hash)
+            .opt("size", self.size)
+    }
+}
+
+/// Parameters of `get`, `get_reader` and `get_to`.
+#[derive(Clone, Debug)]
+pub struct Get {
+    path: String,
+    archive: Option<bool>,
+    compress: Option<bool>,
+    compression_level: Option<u32>,
+}
+
+impl Get {
+
+    pub fn new(path: &str) -> Get {
+        Get { path: path.to_string(), archive: None, compress: None, compression_level: None }
+    }
+
+    /// Has the daemon send a tar archive even for a single file. `get` then writes the archive
+    /// to the output path instead of unpacking it.
+    pub fn archive(&mut self, inp: bool) -> &mut Get {
+        self.archive = Some(inp);
+        self
+    }
+
+    /// Has the daemon gzip what it sends.
+    pub fn compress(&mut self, inp: bool) -> &mut Get {
+        self.compress = Some(inp);
+        self
+    }
+
+    /// The gzip level, from 1 to 9.
+    pub fn compression_level(&mut self, inp: u32) -> &mut Get {
+        self.compression_level = Some(inp);
+        self
+    }
+
+    pub(crate) fn is_archive(&self) -> bool {
+        self.archive.unwrap_or(false)
+    }
+
+    pub(crate) fn is_compressed(&self) -> bool {
+        self.compress.unwrap_or(false)
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .arg(&self.path)
+            .opt("archive", self.archive)
+            .opt("compress", self.compress)
+            .opt("compression-level", self.compression_level)
+    }
+}
+
+/// Parameters of `id`. Without a peer it describes the daemon's own node.
+#[derive(Clone, Debug, Default)]
+pub struct Id {
+    peer: Option<String>,
+    format: Option<String>,
+}
+
+impl Id {
+
+    pub fn new() -> Id {
+        Default::default()
+    }
+
+    pub fn peer(&mut self, inp: &str) -> &mut Id {
+        self.peer = Some(inp.to_string());
+        self
+    }
+
+    /// A template for the output, e.g. `<id>`.
+    pub fn format(&mut self, inp: &str) -> &mut Id {
+        self.format = Some(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        let args = match self.peer {
+            Some(ref peer) => Query::new().arg(peer),
+            None => Query::new(),
+        };
+        args.opt("format", self.format.as_ref())
+    }
+}
+
+/// Parameters of `key_gen`.
+#[derive(Clone, Debug)]
+pub struct KeyGen {
+    name: String,
+    key_type: Option<String>,
+    size: Option<i64>,
+}
+
+impl KeyGen {
+
+    pub fn new(name: &str) -> KeyGen {
+        KeyGen { name: name.to_string(), key_type: None, size: None }
+    }
+
+    /// The key algorithm, e.g. `ed25519` or `rsa`.
+    pub fn key_type(&mut self, inp: &str) -> &mut KeyGen {
+        self.key_type = Some(inp.to_string());
+        self
+    }
+
+    /// The key size in bits, for the algorithms that have one.
+    pub fn size(&mut self, inp: i64) -> &mut KeyGen {
+        self.size = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.name).opt("type", self.key_type.as_ref()).opt("size", self.size)
+    }
+}
+
+/// Parameters of `key_list`.
+#[derive(Clone, Debug, Default)]
+pub struct KeyList {
+    long: Option<bool>,
+}
+
+impl KeyList {
+
+    pub fn new() -> KeyList {
+        Default::default()
+    }
+
+    /// Includes the ID of every key, not just its name.
+    pub fn long(&mut self, inp: bool) -> &mut KeyList {
+        self.long = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("l", self.long)
+    }
+}
+
+/// Parameters of `key_rename`.
+#[derive(Clone, Debug)]
+pub struct KeyRename {
+    old: String,
+    new: String,
+    force: Option<bool>,
+}
+
+impl KeyRename {
+
+    pub fn new(old: &str, new: &str) -> KeyRename {
+        KeyRename { old: old.to_string(), new: new.to_string(), force: None }
+    }
+
+    /// Replaces a key that already has the new name.
+    pub fn force(&mut self, inp: bool) -> &mut KeyRename {
+        self.force = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.old).arg(&self.new).opt("force", self.force)
+    }
+}
+
+/// Parameters of `ls`.
+#[derive(Clone, Debug)]
+pub struct Ls {
+    path: String,
+    headers: Option<bool>,
+    resolve_type: Option<bool>,
+}
+
+impl Ls {
+
+    pub fn new(path: &str) -> Ls {
+        Ls { path: path.to_string(), headers: None, resolve_type: None }
+    }
+
+    /// Includes the table headers in the daemon's text output.
+    pub fn headers(&mut self, inp: bool) -> &mut Ls {
+        self.headers = Some(inp);
+        self
+    }
+
+    /// Looks up whether each link is a file or a directory, which may fetch the linked nodes.
+    pub fn resolve_type(&mut self, inp: bool) -> &mut Ls {
+        self.resolve_type = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("headers", self.headers).opt("resolve-type", self.resolve_type)
+    }
+}
+
+/// Parameters of `mount`. Without paths the daemon mounts where its config says.
+#[derive(Clone, Debug, Default)]
+pub struct Mount {
+    ipfs_path: Option<String>,
+    ipns_path: Option<String>,
+}
+
+impl Mount {
+
+    pub fn new() -> Mount {
+        Default::default()
+    }
+
+    /// Where to mount `/ipfs`.
+    pub fn ipfs_path(&mut self, inp: &str) -> &mut Mount {
+        self.ipfs_path = Some(inp.to_string());
+        self
+    }
+
+    /// Where to mount `/ipns`.
+    pub fn ipns_path(&mut self, inp: &str) -> &mut Mount {
+        self.ipns_path = Some(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("ipfs-path", self.ipfs_path.as_ref()).opt("ipns-path", self.ipns_path.as_ref())
+    }
+}
+
+/// Parameters of `name_publish`.
+#[derive(Clone, Debug)]
+pub struct NamePublish {
+    path: String,
+    resolve: Option<bool>,
+    lifetime: Option<String>,
+    ttl: Option<String>,
+    key: Option<String>,
+    allow_offline: Option<bool>,
+}
+
+impl NamePublish {
+
+    pub fn new(path: &str) -> NamePublish {
+        NamePublish {
+            path: path.to_string(),
+            resolve: None,
+            lifetime: None,
+            ttl: None,
+            key: None,
+            allow_offline: None,
+        }
+    }
+
+    /// Checks that the path resolves before publishing it.
+    pub fn resolve(&mut self, inp: bool) -> &mut NamePublish {
+        self.resolve = Some(inp);
+        self
+    }
+
+    /// How long the record stays valid, e.g. `24h`.
+    pub fn lifetime(&mut self, inp: &str) -> &mut NamePublish {
+        self.lifetime = Some(inp.to_string());
+        self
+    }
+
+    /// How long resolvers may cache the record, e.g. `1m`.
+    pub fn ttl(&mut self, inp: &str) -> &mut NamePublish {
+        self.ttl = Some(inp.to_string());
+        self
+    }
+
+    /// The name of the key to publish under, as listed by `key_list`.
+    pub fn key(&mut self, inp: &str) -> &mut NamePublish {
+        self.key = Some(inp.to_string());
+        self
+    }
+
+    /// Publishes even when the daemon is offline.
+    pub fn allow_offline(&mut self, inp: bool) -> &mut NamePublish {
+        self.allow_offline = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .arg(&self.path)
+            .opt("resolve", self.resolve)
+            .opt("lifetime", self.lifetime.as_ref())
+            .opt("ttl", self.ttl.as_ref())
+            .opt("key", self.key.as_ref())
+            .opt("allow-offline", self.allow_offline)
+    }
+}
+
+/// Parameters of `name_resolve`.
+#[derive(Clone, Debug)]
+pub struct NameResolve {
+    name: String,
+    recursive: Option<bool>,
+    nocache: Option<bool>,
+}
+
+impl NameResolve {
+
+    pub fn new(name: &str) -> NameResolve {
+        NameResolve { name: name.to_string(), recursive: None, nocache: None }
+    }
+
+    /// Keeps resolving until the result is no longer an IPNS name.
+    pub fn recursive(&mut self, inp: bool) -> &mut NameResolve {
+        self.recursive = Some(inp);
+        self
+    }
+
+    /// Skips the daemon's cache of resolved names.
+    pub fn nocache(&mut self, inp: bool) -> &mut NameResolve {
+        self.nocache = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.name).opt("recursive", self.recursive).opt("nocache", self.nocache)
+    }
+}
+
+/// Parameters of `object_diff`.
+#[derive(Clone, Debug)]
+pub struct ObjectDiff {
+    left: String,
+    right: String,
+    verbose: Option<bool>,
+}
+
+impl ObjectDiff {
+
+    pub fn new(left: &str, right: &str) -> ObjectDiff {
+        ObjectDiff { left: left.to_string(), right: right.to_string(), verbose: None }
+    }
+
+    /// Has the daemon describe every change in its text output.
+    pub fn verbose(&mut self, inp: bool) -> &mut ObjectDiff {
+        self.verbose = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.left).arg(&self.right).opt("verbose", self.verbose)
+    }
+}
+
+/// Parameters of `object_links`.
+#[derive(Clone, Debug)]
+pub struct ObjectLinks {
+    key: String,
+    headers: Option<bool>,
+}
+
+impl ObjectLinks {
+
+    pub fn new(key: &str) -> ObjectLinks {
+        ObjectLinks { key: key.to_string(), headers: None }
+    }
+
+    /// Includes the table headers in the daemon's text output.
+    pub fn headers(&mut self, inp: bool) -> &mut ObjectLinks {
+        self.headers = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.key).opt("headers", self.headers)
+    }
+}
+
+/// Parameters of `object_patch_add_link`.
+#[derive(Clone, Debug)]
+pub struct ObjectPatchAddLink {
+    hash: String,
+    name: String,
+    target: String,
+    create: Option<bool>,
+}
+
+impl ObjectPatchAddLink {
+
+    /// Links `target` into the object `hash` under `name`, which may be a path.
+    pub fn new(hash: &str, name: &str, target: &str) -> ObjectPatchAddLink {
+        ObjectPatchAddLink { hash: hash.to_string(), name: name.to_string(), target: target.to_string(), create: None }
+    }
+
+    /// Creates the intermediate directories of a `name` that is a path.
+    pub fn create(&mut self, inp: bool) -> &mut ObjectPatchAddLink {
+        self.create = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.hash).arg(&self.name).arg(&self.target).opt("create", self.create)
+    }
+}
+
+/// Parameters of `object_put`.
+#[derive(Clone, Debug, Default)]
+pub struct ObjectPut {
+    inputenc: Option<String>,
+    datafieldenc: Option<String>,
+    pin: Option<bool>,
+}
+
+impl ObjectPut {
+
+    pub fn new() -> ObjectPut {
+        Default::default()
+    }
+
+    /// The encoding of the uploaded object, `json` or `protobuf`.
+    pub fn inputenc(&mut self, inp: &str) -> &mut ObjectPut {
+        self.inputenc = Some(inp.to_string());
+        self
+    }
+
+    /// The encoding of the object's data field, `text` or `base64`.
+    pub fn datafieldenc(&mut self, inp: &str) -> &mut ObjectPut {
+        self.datafieldenc = Some(inp.to_string());
+        self
+    }
+
+    /// Pins the object once it is stored.
+    pub fn pin(&mut self, inp: bool) -> &mut ObjectPut {
+        self.pin = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .opt("inputenc", self.inputenc.as_ref())
+            .opt("datafieldenc", self.datafieldenc.as_ref())
+            .opt("pin", self.pin)
+    }
+}
+
+/// Parameters of `pin_add` and `pin_add_progress`.
+#[derive(Clone, Debug)]
+pub struct PinAdd {
+    path: String,
+    recursive: Option<bool>,
+}
+
+impl PinAdd {
+
+    pub fn new(path: &str) -> PinAdd {
+        PinAdd { path: path.to_string(), recursive: None }
+    }
+
+    /// Pins everything below the path too. The daemon pins recursively by default.
+    pub fn recursive(&mut self, inp: bool) -> &mut PinAdd {
+        self.recursive = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("recursive", self.recursive)
+    }
+}
+
+/// Parameters of `pin_ls`. Without a path it lists every pin.
+#[derive(Clone, Debug, Default)]
+pub struct PinLs {
+    path: Option<String>,
+    pin_type: Option<String>,
+    quiet: Option<bool>,
+}
+
+impl PinLs {
+
+    pub fn new() -> PinLs {
+        Default::default()
+    }
+
+    pub fn path(&mut self, inp: &str) -> &mut PinLs {
+        self.path = Some(inp.to_string());
+        self
+    }
+
+    /// Only lists pins of this kind: `direct`, `indirect`, `recursive` or `all`.
+    pub fn pin_type(&mut self, inp: &str) -> &mut PinLs {
+        self.pin_type = Some(inp.to_string());
+        self
+    }
+
+    /// Leaves the pin types out of the daemon's reply.
+    pub fn quiet(&mut self, inp: bool) -> &mut PinLs {
+        self.quiet = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        let args = match self.path {
+            Some(ref path) => Query::new().arg(path),
+            None => Query::new(),
+        };
+        args.opt("type", self.pin_type.as_ref()).opt("quiet", self.quiet)
+    }
+}
+
+/// Parameters of `pin_remote_add`.
+#[derive(Clone, Debug)]
+pub struct PinRemoteAdd {
+    path: String,
+    service: String,
+    name: Option<String>,
+    background: Option<bool>,
+}
+
+impl PinRemoteAdd {
+
+    /// Pins `path` with the remote pinning service named `service`, as added by
+    /// `pin_remote_service_add`.
+    pub fn new(path: &str, service: &str) -> PinRemoteAdd {
+        PinRemoteAdd { path: path.to_string(), service: service.to_string(), name: None, background: None }
+    }
+
+    /// A name for the pin, shown by the service.
+    pub fn name(&mut self, inp: &str) -> &mut PinRemoteAdd {
+        self.name = Some(inp.to_string());
+        self
+    }
+
+    /// Returns once the service has queued the pin instead of waiting for it to be pinned.
+    pub fn background(&mut self, inp: bool) -> &mut PinRemoteAdd {
+        self.background = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .arg(&self.path)
+            .pair("service", &self.service)
+            .opt("name", self.name.as_ref())
+            .opt("background", self.background)
+    }
+}
+
+/// Parameters of `pin_remote_ls`. Without filters the service reports its pinned objects.
+#[derive(Clone, Debug)]
+pub struct PinRemoteLs {
+    service: String,
+    name: Option<String>,
+    cids: Vec<String>,
+    statuses: Vec<String>,
+}
+
+impl PinRemoteLs {
+
+    pub fn new(service: &str) -> PinRemoteLs {
+        PinRemoteLs { service: service.to_string(), name: None, cids: Vec::new(), statuses: Vec::new() }
+    }
+
+    /// Only lists pins with this name.
+    pub fn name(&mut self, inp: &str) -> &mut PinRemoteLs {
+        self.name = Some(inp.to_string());
+        self
+    }
+
+    /// Only lists pins of this CID. May be repeated.
+    pub fn cid(&mut self, inp: &str) -> &mut PinRemoteLs {
+        self.cids.push(inp.to_string());
+        self
+    }
+
+    /// Only lists pins in this state: `queued`, `pinning`, `pinned` or `failed`. May be repeated.
+    pub fn status(&mut self, inp: &str) -> &mut PinRemoteLs {
+        self.statuses.push(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        remote_filters(Query::new().pair("service", &self.service), &self.name, &self.cids, &self.statuses)
+    }
+}
+
+/// Parameters of `pin_remote_rm`.
+#[derive(Clone, Debug)]
+pub struct PinRemoteRm {
+    service: String,
+    name: Option<String>,
+    cids: Vec<String>,
+    statuses: Vec<String>,
+    force: Option<bool>,
+}
+
+impl PinRemoteRm {
+
+    pub fn new(service: &str) -> PinRemoteRm {
+        PinRemoteRm { service: service.to_string(), name: None, cids: Vec::new(), statuses: Vec::new(), force: None }
+    }
+
+    /// Only removes pins with this name.
+    pub fn name(&mut self, inp: &str) -> &mut PinRemoteRm {
+        self.name = Some(inp.to_string());
+        self
+    }
+
+    /// Only removes pins of this CID. May be repeated.
+    pub fn cid(&mut self, inp: &str) -> &mut PinRemoteRm {
+        self.cids.push(inp.to_string());
+        self
+    }
+
+    /// Only removes pins in this state. May be repeated.
+    pub fn status(&mut self, inp: &str) -> &mut PinRemoteRm {
+        self.statuses.push(inp.to_string());
+        self
+    }
+
+    /// Removes every pin the filters match. Without it, matching more than one pin fails.
+    pub fn force(&mut self, inp: bool) -> &mut PinRemoteRm {
+        self.force = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        let args = remote_filters(Query::new().pair("service", &self.service), &self.name, &self.cids, &self.statuses);
+        args.opt("force", self.force)
+    }
+}
+
+/// The filters shared by `pin/remote/ls` and `pin/remote/rm`.
+fn remote_filters(mut args: Query, name: &Option<String>, cids: &[String], statuses: &[String]) -> Query {
+    args = args.opt("name", name.as_ref());
+    for cid in cids {
+        args = args.pair("cid", cid);
+    }
+    for status in statuses {
+        args = args.pair("status", status);
+    }
+    args
+}
+
+/// Parameters of `pin_rm`.
+#[derive(Clone, Debug)]
+pub struct PinRm {
+    path: String,
+    recursive: Option<bool>,
+}
+
+impl PinRm {
+
+    pub fn new(path: &str) -> PinRm {
+        PinRm { path: path.to_string(), recursive: None }
+    }
+
+    /// Removes a recursive pin rather than a direct one. The daemon does by default.
+    pub fn recursive(&mut self, inp: bool) -> &mut PinRm {
+        self.recursive = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.path).opt("recursive", self.recursive)
+    }
+}
+
+/// Parameters of `pin_update`.
+#[derive(Clone, Debug)]
+pub struct PinUpdate {
+    from: String,
+    to: String,
+    unpin: Option<bool>,
+}
+
+impl PinUpdate {
+
+    pub fn new(from: &str, to: &str) -> PinUpdate {
+        PinUpdate { from: from.to_string(), to: to.to_string(), unpin: None }
+    }
+
+    /// Removes the old pin once the new one is in place. The daemon does by default.
+    pub fn unpin(&mut self, inp: bool) -> &mut PinUpdate {
+        self.unpin = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.from).arg(&self.to).opt("unpin", self.unpin)
+    }
+}
+
+/// Parameters of `pin_verify`.
+#[derive(Clone, Debug, Default)]
+pub struct PinVerify {
+    verbose: Option<bool>,
+}
+
+impl PinVerify {
+
+    pub fn new() -> PinVerify {
+        Default::default()
+    }
+
+    /// Reports healthy pins too, not only the broken ones.
+    pub fn verbose(&mut self, inp: bool) -> &mut PinVerify {
+        self.verbose = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("verbose", self.verbose)
+    }
+}
+
+/// Parameters of `ping` and `ping_summary`.
+#[derive(Clone, Debug)]
+pub struct Ping {
+    peer: String,
+    count: Option<i32>,
+}
+
+impl Ping {
+
+    pub fn new(peer: &str) -> Ping {
+        Ping { peer: peer.to_string(), count: None }
+    }
+
+    /// How many pings to send. The daemon sends 10.
+    pub fn count(&mut self, inp: i32) -> &mut Ping {
+        self.count = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.peer).opt("count", self.count)
+    }
+}
+
+/// Parameters of `repo_gc`.
+#[derive(Clone, Debug, Default)]
+pub struct RepoGc {
+    quiet: Option<bool>,
+    stream_errors: Option<bool>,
+}
+
+impl RepoGc {
+
+    pub fn new() -> RepoGc {
+        Default::default()
+    }
+
+    /// Leaves the removed CIDs out of the daemon's reply.
+    pub fn quiet(&mut self, inp: bool) -> &mut RepoGc {
+        self.quiet = Some(inp);
+        self
+    }
+
+    /// Reports errors as they happen instead of stopping at the first one.
+    pub fn stream_errors(&mut self, inp: bool) -> &mut RepoGc {
+        self.stream_errors = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("quiet", self.quiet).opt("stream-errors", self.stream_errors)
+    }
+}
+
+/// Parameters of `repo_stat` and `stats_repo`.
+#[derive(Clone, Debug, Default)]
+pub struct RepoStat {
+    human: Option<bool>,
+}
+
+impl RepoStat {
+
+    pub fn new() -> RepoStat {
+        Default::default()
+    }
+
+    /// Has the daemon write sizes in MiB in its text output.
+    pub fn human(&mut self, inp: bool) -> &mut RepoStat {
+        self.human = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("human", self.human)
+    }
+}
+
+/// Parameters of `repo_version`.
+#[derive(Clone, Debug, Default)]
+pub struct RepoVersion {
+    quiet: Option<bool>,
+}
+
+impl RepoVersion {
+
+    pub fn new() -> RepoVersion {
+        Default::default()
+    }
+
+    /// Leaves the label out of the daemon's text output.
+    pub fn quiet(&mut self, inp: bool) -> &mut RepoVersion {
+        self.quiet = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("quiet", self.quiet)
+    }
+}
+
+/// Parameters of `resolve`.
+#[derive(Clone, Debug)]
+pub struct Resolve {
+    name: String,
+    recursive: Option<bool>,
+}
+
+impl Resolve {
+
+    pub fn new(name: &str) -> Resolve {
+        Resolve { name: name.to_string(), recursive: None }
+    }
+
+    /// Keeps resolving until the result is an `/ipfs` path. The daemon does by default.
+    pub fn recursive(&mut self, inp: bool) -> &mut Resolve {
+        self.recursive = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().arg(&self.name).opt("recursive", self.recursive)
+    }
+}
+
+/// Parameters of `stats_bw` and `stats_bw_poll`. Without a peer or protocol the totals are
+/// reported.
+#[derive(Clone, Debug, Default)]
+pub struct StatsBw {
+    peer: Option<String>,
+    proto: Option<String>,
+}
+
+impl StatsBw {
+
+    pub fn new() -> StatsBw {
+        Default::default()
+    }
+
+    /// Only counts traffic with this peer.
+    pub fn peer(&mut self, inp: &str) -> &mut StatsBw {
+        self.peer = Some(inp.to_string());
+        self
+    }
+
+    /// Only counts traffic of this protocol, e.g. `/ipfs/bitswap/1.1.0`.
+    pub fn proto(&mut self, inp: &str) -> &mut StatsBw {
+        self.proto = Some(inp.to_string());
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new().opt("peer", self.peer.as_ref()).opt("proto", self.proto.as_ref())
+    }
+}
+
+/// Parameters of `version`.
+#[derive(Clone, Debug, Default)]
+pub struct Version {
+    number: Option<bool>,
+    commit: Option<bool>,
+    repo: Option<bool>,
+    all: Option<bool>,
+}
+
+impl Version {
+
+    pub fn new() -> Version {
+        Default::default()
+    }
+
+    /// Only reports the version number.
+    pub fn number(&mut self, inp: bool) -> &mut Version {
+        self.number = Some(inp);
+        self
+    }
+
+    /// Includes the commit hash.
+    pub fn commit(&mut self, inp: bool) -> &mut Version {
+        self.commit = Some(inp);
+        self
+    }
+
+    /// Includes the repo version.
+    pub fn repo(&mut self, inp: bool) -> &mut Version {
+        self.repo = Some(inp);
+        self
+    }
+
+    /// Includes everything the daemon knows about its build.
+    pub fn all(&mut self, inp: bool) -> &mut Version {
+        self.all = Some(inp);
+        self
+    }
+
+    pub(crate) fn args(&self) -> Query {
+        Query::new()
+            .opt("number", self.number)
+            .opt("commit", self.commit)
+            .opt("repo", self.repo)
+            .opt("all", self.all)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn unset_parameters_are_left_out() {
+        assert_eq!("arg=%2Fipfs%2FQm", NamePublish::new("/ipfs/Qm").args().to_string());
+        assert!(Version::new().args().is_empty());
+        assert!(PinLs::new().args().is_empty());
+        assert!(Mount::new().args().is_empty());
+        assert!(DiagNet::new().args().is_empty());
+        assert_eq!("arg=%2Fdocs", FilesStat::new("/docs").args().to_string());
+        assert_eq!("arg=%2Fipfs%2FQm", PinRm::new("/ipfs/Qm").args().to_string());
+        assert_eq!("arg=%2Fnotes.txt", FilesRead::new("/notes.txt").args().to_string());
+        assert_eq!("arg=example.com", Dns::new("example.com").args().to_string());
+        assert!(RepoStat::new().args().is_empty());
+    }
+
+    #[test]
+    fn set_parameters_are_sent() {
+        let l = NamePublish::new("/ipfs/Qm").lifetime("24h").key("mykey").allow_offline(true).args();
+        assert_eq!("arg=%2Fipfs%2FQm&lifetime=24h&key=mykey&allow-offline=true", l.to_string());
+        let l = Get::new("Qm").compress(true).compression_level(6).args();
+        assert_eq!("arg=Qm&compress=true&compression-level=6", l.to_string());
+        let l = Version::new().number(true).all(false).args();
+        assert_eq!("number=true&all=false", l.to_string());
+        let l = PinRemoteLs::new("pinata").cid("QmA").cid("QmB").status("pinned").args();
+        assert_eq!("service=pinata&cid=QmA&cid=QmB&status=pinned", l.to_string());
+        let l = Mount::new().ipns_path("/mnt/ipns").args();
+        assert_eq!("ipns-path=%2Fmnt%2Fipns", l.to_string());
+        let l = DhtPut::new("/ipns/k", "v").verbose(false).args();
+        assert_eq!("arg=%2Fipns%2Fk&arg=v&verbose=false", l.to_string());
+        let l = ObjectPatchAddLink::new("QmA", "a/b", "QmB").create(true).args();
+        assert_eq!("arg=QmA&arg=a%2Fb&arg=QmB&create=true", l.to_string());
+        let l = Resolve::new("/ipns/k").recursive(false).args();
+        assert_eq!("arg=%2Fipns%2Fk&recursive=false", l.to_string());
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+/// The query string of a call, percent-encoded as it is written out.
+///
+/// Parameters keep their order and may repeat, which is how the daemon takes several `arg`s.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Query {
+    pairs: Vec<(String, String)>,
+}
+
+impl Query {
+    pub fn new() -> Query {
+        Default::default()
+    }
+
+    /// Adds a positional argument.
+    pub fn arg<V: ToString>(self, value: V) -> Query {
+        self.pair("arg", value)
+    }
+
+    pub fn pair<V: ToString>(mut self, key: &str, value: V) -> Query {
+        self.pairs.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds `key` only when `value` is set, leaving the default up to the daemon otherwise.
+    pub fn opt<V: ToString>(self, key: &str, value: Option<V>) -> Query {
+        match value {
+            Some(value) => self.pair(key, value),
+            None => self,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (key, value)) in self.pairs.iter().enumerate() {
+            if i > 0 {
+                f.write_str("&")?;
+            }
+            write!(f, "{}={}", percent_encode(key, b""), percent_encode(value, b""))?;
+        }
+        Ok(())
+    }
+}
+
+/// Escapes every byte of `inp` other than unreserved characters and those in `keep`.
+pub(crate) fn percent_encode(inp: &str, keep: &[u8]) -> String {
+    let mut escaped = String::with_capacity(inp.len());
+    for b in inp.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => escaped.push(b as char),
+            _ if keep.contains(&b) => escaped.push(b as char),
+            _ => escaped.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn values_are_escaped() {
+        let q = Query::new().arg("/my docs/a&b#c?d").arg("é").pair("parents", true);
+        assert_eq!("arg=%2Fmy%20docs%2Fa%26b%23c%3Fd&arg=%C3%A9&parents=true", q.to_string());
+    }
+
+    #[test]
+    fn unset_options_are_left_out() {
+        let q = Query::new().opt("count", None::<u64>).opt("offset", Some(3)).opt("pin", Some(false));
+        assert_eq!("offset=3&pin=false", q.to_string());
+    }
+}
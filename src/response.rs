@@ -0,0 +1,1456 @@
+use std::collections::BTreeMap;
+use std::str;
+use std::time::Duration;
+
+use json::{self, JsonValue};
+
+use rustc_serialize::base64::FromBase64;
+
+use crate::error::{IpfsError, Result};
+use crate::multibase;
+
+/// Decodes one JSON value coming from the daemon into a typed response.
+pub(crate) trait FromJson: Sized {
+    fn from_json(v: &JsonValue) -> Result<Self>;
+}
+
+pub(crate) fn parse(bytes: &[u8]) -> Result<JsonValue> {
+    let text = str::from_utf8(bytes)
+        .map_err(|_| IpfsError::Json(json::Error::WrongType("response is not utf-8".to_string())))?;
+    Ok(json::parse(text)?)
+}
+
+/// Decodes a body holding a single JSON object.
+pub(crate) fn decode<T: FromJson>(bytes: &[u8]) -> Result<T> {
+    T::from_json(&parse(bytes)?)
+}
+
+/// Decodes a newline-delimited stream of JSON objects.
+pub(crate) fn decode_lines<T: FromJson>(bytes: &[u8]) -> Result<Vec<T>> {
+    bytes.split(|b| *b == b'\n')
+        .filter(|line| !line.iter().all(|b| b.is_ascii_whitespace()))
+        .map(|line| decode(line))
+        .collect()
+}
+
+/// Decodes a plain text body, e.g. what `multibase_encode` replies with.
+pub(crate) fn decode_text(bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| IpfsError::Json(json::Error::WrongType("response is not utf-8".to_string())))
+}
+
+/// Keeps the final object of a newline-delimited reply, e.g. the root of an `add`.
+pub(crate) fn last<T>(mut items: Vec<T>) -> Result<T> {
+    items.pop().ok_or_else(|| IpfsError::Json(json::Error::WrongType("empty response".to_string())))
+}
+
+// The helpers below decode bodies whose only interesting part is a single field,
+// e.g. the `{"Strings": [...]}` list most simple endpoints reply with.
+
+pub(crate) fn decode_string(bytes: &[u8], key: &str) -> Result<String> {
+    string(&parse(bytes)?, key)
+}
+
+pub(crate) fn decode_bool(bytes: &[u8], key: &str) -> Result<bool> {
+    boolean(&parse(bytes)?, key)
+}
+
+pub(crate) fn decode_strings(bytes: &[u8], key: &str) -> Result<Vec<String>> {
+    strings(&parse(bytes)?, key)
+}
+
+pub(crate) fn decode_cid(bytes: &[u8], key: &str) -> Result<String> {
+    cid(&parse(bytes)?, key)
+}
+
+pub(crate) fn decode_cids(bytes: &[u8], key: &str) -> Result<Vec<String>> {
+    cids(&parse(bytes)?, key)
+}
+
+fn topic(encoded: &str) -> Option<String> {
+    multibase::decode(encoded).and_then(|t| String::from_utf8(t).ok())
+}
+
+/// Decodes the topic names the daemon lists under `Strings`. Names that are not multibase are
+/// kept as sent, which is how daemons before Kubo 0.11 send all of them.
+pub(crate) fn decode_topics(bytes: &[u8], encoding: Option<PubsubEncoding>) -> Result<Vec<String>> {
+    let topics = decode_strings(bytes, "Strings")?;
+    if encoding == Some(PubsubEncoding::Base64) {
+        return Ok(topics);
+    }
+    Ok(topics.into_iter().map(|t| topic(&t).unwrap_or(t)).collect())
+}
+
+pub(crate) fn decode_list<T: FromJson>(bytes: &[u8], key: &str) -> Result<Vec<T>> {
+    list(&parse(bytes)?, key)
+}
+
+/// Decodes a body that is a bare JSON array, as the `cid/*` and `multibase/*` listings are.
+pub(crate) fn decode_array<T: FromJson>(bytes: &[u8]) -> Result<Vec<T>> {
+    match parse(bytes)? {
+        JsonValue::Array(ref items) => items.iter().map(T::from_json).collect(),
+        _ => Err(wrong_type("response", "a list")),
+    }
+}
+
+fn wrong_type(key: &str, expected: &str) -> IpfsError {
+    IpfsError::Json(json::Error::WrongType(format!("expected `{}` to be {}", key, expected)))
+}
+
+pub(crate) fn string(v: &JsonValue, key: &str) -> Result<String> {
+    match v[key] {
+        JsonValue::Null => Ok(String::new()),
+        ref s => s.as_str().map(|s| s.to_string()).ok_or_else(|| wrong_type(key, "a string")),
+    }
+}
+
+pub(crate) fn opt_string(v: &JsonValue, key: &str) -> Result<Option<String>> {
+    string(v, key).map(|s| if s.is_empty() { None } else { Some(s) })
+}
+
+pub(crate) fn number_u64(v: &JsonValue, key: &str) -> Result<u64> {
+    match v[key] {
+        JsonValue::Null => Ok(0),
+        // Some endpoints send sizes as strings.
+        ref n if n.is_string() => n.as_str().unwrap().parse().map_err(|_| wrong_type(key, "a number")),
+        ref n => n.as_u64().ok_or_else(|| wrong_type(key, "a number")),
+    }
+}
+
+pub(crate) fn number_i64(v: &JsonValue, key: &str) -> Result<i64> {
+    match v[key] {
+        JsonValue::Null => Ok(0),
+        ref n => n.as_i64().ok_or_else(|| wrong_type(key, "a number")),
+    }
+}
+
+pub(crate) fn number_f64(v: &JsonValue, key: &str) -> Result<f64> {
+    match v[key] {
+        JsonValue::Null => Ok(0.0),
+        ref n => n.as_f64().ok_or_else(|| wrong_type(key, "a number")),
+    }
+}
+
+pub(crate) fn boolean(v: &JsonValue, key: &str) -> Result<bool> {
+    match v[key] {
+        JsonValue::Null => Ok(false),
+        ref b => b.as_bool().ok_or_else(|| wrong_type(key, "a boolean")),
+    }
+}
+
+pub(crate) fn strings(v: &JsonValue, key: &str) -> Result<Vec<String>> {
+    match v[key] {
+        JsonValue::Null => Ok(Vec::new()),
+        JsonValue::Array(ref items) => items.iter()
+            .map(|s| s.as_str().map(|s| s.to_string()).ok_or_else(|| wrong_type(key, "a list of strings")))
+            .collect(),
+        _ => Err(wrong_type(key, "a list of strings")),
+    }
+}
+
+/// Newer daemons wrap CIDs in a `{"/": "Qm..."}` link object, older ones send a plain string.
+fn cid_value(v: &JsonValue, key: &str) -> Result<String> {
+    match *v {
+        JsonValue::Null => Ok(String::new()),
+        JsonValue::Object(_) => string(v, "/"),
+        ref s => s.as_str().map(|s| s.to_string()).ok_or_else(|| wrong_type(key, "a cid")),
+    }
+}
+
+pub(crate) fn cid(v: &JsonValue, key: &str) -> Result<String> {
+    cid_value(&v[key], key)
+}
+
+pub(crate) fn opt_cid(v: &JsonValue, key: &str) -> Result<Option<String>> {
+    cid(v, key).map(|s| if s.is_empty() { None } else { Some(s) })
+}
+
+pub(crate) fn cids(v: &JsonValue, key: &str) -> Result<Vec<String>> {
+    match v[key] {
+        JsonValue::Null => Ok(Vec::new()),
+        JsonValue::Array(ref items) => items.iter().map(|c| cid_value(c, key)).collect(),
+        _ => Err(wrong_type(key, "a list of cids")),
+    }
+}
+
+pub(crate) fn list<T: FromJson>(v: &JsonValue, key: &str) -> Result<Vec<T>> {
+    match v[key] {
+        JsonValue::Null => Ok(Vec::new()),
+        JsonValue::Array(ref items) => items.iter().map(T::from_json).collect(),
+        _ => Err(wrong_type(key, "a list")),
+    }
+}
+
+pub(crate) fn map<T: FromJson>(v: &JsonValue, key: &str) -> Result<BTreeMap<String, T>> {
+    match v[key] {
+        JsonValue::Null => Ok(BTreeMap::new()),
+        JsonValue::Object(_) => v[key].entries()
+            .map(|(k, item)| T::from_json(item).map(|item| (k.to_string(), item)))
+            .collect(),
+        _ => Err(wrong_type(key, "an object")),
+    }
+}
+
+impl FromJson for String {
+    fn from_json(v: &JsonValue) -> Result<String> {
+        v.as_str().map(|s| s.to_string()).ok_or_else(|| wrong_type("value", "a string"))
+    }
+}
+
+
+//------------------------------------------ RESPONSES ------------------------------------------
+
+
+/// One object added by `add` or `tar_add`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddResponse {
+    pub name: String,
+    pub hash: String,
+    pub size: u64,
+}
+
+impl FromJson for AddResponse {
+    fn from_json(v: &JsonValue) -> Result<AddResponse> {
+        Ok(AddResponse {
+            name: string(v, "Name")?,
+            hash: string(v, "Hash")?,
+            size: number_u64(v, "Size")?,
+        })
+    }
+}
+
+/// One event reported by `add_progress` and `add_path_progress`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AddEvent {
+    /// The daemon has received `bytes` bytes of the file `name` so far.
+    Progress { name: String, bytes: u64 },
+    /// `name` is fully added.
+    Added(AddResponse),
+}
+
+impl FromJson for AddEvent {
+    fn from_json(v: &JsonValue) -> Result<AddEvent> {
+        if v["Hash"].is_null() {
+            Ok(AddEvent::Progress { name: string(v, "Name")?, bytes: number_u64(v, "Bytes")? })
+        } else {
+            AddResponse::from_json(v).map(AddEvent::Added)
+        }
+    }
+}
+
+/// Everything added by `add_path`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddPathResponse {
+    /// One entry per added file and directory, the root last.
+    pub entries: Vec<AddResponse>,
+    /// The CID of the root: the added directory, or the wrapping one.
+    pub root: String,
+}
+
+pub(crate) fn decode_add_path(bytes: &[u8]) -> Result<AddPathResponse> {
+    // Progress lines come without a hash.
+    let entries: Vec<AddResponse> = decode_lines::<AddResponse>(bytes)?
+        .into_iter().filter(|e| !e.hash.is_empty()).collect();
+    let root = last(entries.clone())?.hash;
+    Ok(AddPathResponse { entries, root })
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitswapLedgerResponse {
+    pub peer: String,
+    pub value: f64,
+    pub sent: u64,
+    pub recv: u64,
+    pub exchanged: u64,
+}
+
+impl FromJson for BitswapLedgerResponse {
+    fn from_json(v: &JsonValue) -> Result<BitswapLedgerResponse> {
+        Ok(BitswapLedgerResponse {
+            peer: string(v, "Peer")?,
+            value: number_f64(v, "Value")?,
+            sent: number_u64(v, "Sent")?,
+            recv: number_u64(v, "Recv")?,
+            exchanged: number_u64(v, "Exchanged")?,
+        })
+    }
+}
+
+/// Returned by both `bitswap_stat` and `stats_bitswap`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BitswapStatResponse {
+    pub provide_buf_len: i64,
+    pub wantlist: Vec<String>,
+    pub peers: Vec<String>,
+    pub blocks_received: u64,
+    pub data_received: u64,
+    pub blocks_sent: u64,
+    pub data_sent: u64,
+    pub dup_blks_received: u64,
+    pub dup_data_received: u64,
+    pub messages_received: u64,
+}
+
+impl FromJson for BitswapStatResponse {
+    fn from_json(v: &JsonValue) -> Result<BitswapStatResponse> {
+        Ok(BitswapStatResponse {
+            provide_buf_len: number_i64(v, "ProvideBufLen")?,
+            wantlist: cids(v, "Wantlist")?,
+            peers: strings(v, "Peers")?,
+            blocks_received: number_u64(v, "BlocksReceived")?,
+            data_received: number_u64(v, "DataReceived")?,
+            blocks_sent: number_u64(v, "BlocksSent")?,
+            data_sent: number_u64(v, "DataSent")?,
+            dup_blks_received: number_u64(v, "DupBlksReceived")?,
+            dup_data_received: number_u64(v, "DupDataReceived")?,
+            messages_received: number_u64(v, "MessagesReceived")?,
+        })
+    }
+}
+
+/// Returned by both `block_put` and `block_stat`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockStatResponse {
+    pub key: String,
+    pub size: u64,
+}
+
+impl FromJson for BlockStatResponse {
+    fn from_json(v: &JsonValue) -> Result<BlockStatResponse> {
+        Ok(BlockStatResponse {
+            key: string(v, "Key")?,
+            size: number_u64(v, "Size")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockRmResponse {
+    pub hash: String,
+    pub error: Option<String>,
+}
+
+impl FromJson for BlockRmResponse {
+    fn from_json(v: &JsonValue) -> Result<BlockRmResponse> {
+        Ok(BlockRmResponse {
+            hash: string(v, "Hash")?,
+            error: opt_string(v, "Error")?,
+        })
+    }
+}
+
+/// Returned by `cid_format` and `cid_base32`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CidFormatResponse {
+    pub cid: String,
+    pub formatted: String,
+    /// Why the CID could not be formatted, if it could not.
+    pub error: Option<String>,
+}
+
+impl FromJson for CidFormatResponse {
+    fn from_json(v: &JsonValue) -> Result<CidFormatResponse> {
+        Ok(CidFormatResponse {
+            cid: string(v, "CidStr")?,
+            formatted: string(v, "Formatted")?,
+            error: opt_string(v, "ErrorMsg")?,
+        })
+    }
+}
+
+/// A multibase, multicodec or multihash the daemon knows, as listed by `cid_bases`,
+/// `cid_codecs`, `cid_hashes` and `multibase_list`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CodeAndName {
+    pub code: i64,
+    pub name: String,
+}
+
+impl FromJson for CodeAndName {
+    fn from_json(v: &JsonValue) -> Result<CodeAndName> {
+        Ok(CodeAndName {
+            code: number_i64(v, "Code")?,
+            name: string(v, "Name")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandsResponse {
+    pub name: String,
+    pub subcommands: Vec<CommandsResponse>,
+    pub options: Vec<CommandOption>,
+}
+
+impl FromJson for CommandsResponse {
+    fn from_json(v: &JsonValue) -> Result<CommandsResponse> {
+        Ok(CommandsResponse {
+            name: string(v, "Name")?,
+            subcommands: list(v, "Subcommands")?,
+            options: list(v, "Options")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandOption {
+    pub names: Vec<String>,
+    /// The option's type, e.g. `bool` or `string`, for daemons that report it. Kubo only
+    /// sends the names.
+    pub option_type: Option<String>,
+}
+
+impl FromJson for CommandOption {
+    fn from_json(v: &JsonValue) -> Result<CommandOption> {
+        Ok(CommandOption {
+            names: strings(v, "Names")?,
+            option_type: opt_string(v, "Type")?,
+        })
+    }
+}
+
+/// One root of a CAR file imported by `dag_import`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagImportRoot {
+    pub cid: String,
+    /// Why the root could not be pinned, if it could not.
+    pub pin_error: Option<String>,
+}
+
+/// Keeps the `Root` lines of a `dag/import` reply, skipping the optional statistics.
+pub(crate) fn decode_dag_import(bytes: &[u8]) -> Result<Vec<DagImportRoot>> {
+    let mut roots = Vec::new();
+    for line in bytes.split(|b| *b == b'\n').filter(|line| !line.iter().all(|b| b.is_ascii_whitespace())) {
+        let v = parse(line)?;
+        if !v["Root"].is_null() {
+            roots.push(DagImportRoot {
+                cid: cid(&v["Root"], "Cid")?,
+                pin_error: opt_string(&v["Root"], "PinErrorMsg")?,
+            });
+        }
+    }
+    Ok(roots)
+}
+
+/// Returned by `dag_resolve`: the last node on the path and what is left of the path inside it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagResolveResponse {
+    pub cid: String,
+    pub rem_path: String,
+}
+
+impl FromJson for DagResolveResponse {
+    fn from_json(v: &JsonValue) -> Result<DagResolveResponse> {
+        Ok(DagResolveResponse {
+            cid: cid(v, "Cid")?,
+            rem_path: string(v, "RemPath")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagStatResponse {
+    pub total_size: u64,
+    pub unique_blocks: u64,
+    pub dags: Vec<DagStat>,
+}
+
+impl FromJson for DagStatResponse {
+    fn from_json(v: &JsonValue) -> Result<DagStatResponse> {
+        Ok(DagStatResponse {
+            total_size: number_u64(v, "TotalSize")?,
+            unique_blocks: number_u64(v, "UniqueBlocks")?,
+            dags: list(v, "DagStats")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DagStat {
+    pub cid: String,
+    pub size: u64,
+    pub num_blocks: u64,
+}
+
+impl FromJson for DagStat {
+    fn from_json(v: &JsonValue) -> Result<DagStat> {
+        Ok(DagStat {
+            cid: cid(v, "Cid")?,
+            size: number_u64(v, "Size")?,
+            num_blocks: number_u64(v, "NumBlocks")?,
+        })
+    }
+}
+
+/// One event of the stream the `dht_*` endpoints reply with.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DhtResponse {
+    pub id: String,
+    pub response_type: i64,
+    pub responses: Vec<DhtPeer>,
+    pub extra: String,
+}
+
+impl FromJson for DhtResponse {
+    fn from_json(v: &JsonValue) -> Result<DhtResponse> {
+        Ok(DhtResponse {
+            id: string(v, "ID")?,
+            response_type: number_i64(v, "Type")?,
+            responses: list(v, "Responses")?,
+            extra: string(v, "Extra")?,
+        })
+    }
+}
+
+/// A peer and its addresses, as found by the `dht_*` calls or listed by `swarm_peering_ls`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DhtPeer {
+    pub id: String,
+    pub addrs: Vec<String>,
+}
+
+impl FromJson for DhtPeer {
+    fn from_json(v: &JsonValue) -> Result<DhtPeer> {
+        Ok(DhtPeer {
+            id: string(v, "ID")?,
+            addrs: strings(v, "Addrs")?,
+        })
+    }
+}
+
+/// One event of a DHT query, as yielded by the `dht_*` calls.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DhtEvent {
+    /// A request went out to `peer`.
+    SendingQuery { peer: String },
+    /// `peer` answered with peers closer to the target.
+    PeerResponse { peer: String, closer: Vec<DhtPeer> },
+    /// The peer being looked for was found.
+    FinalPeer(DhtPeer),
+    /// Querying `peer` failed.
+    QueryError { peer: String, error: String },
+    /// `from` knows of these providers of the key.
+    Provider { from: String, providers: Vec<DhtPeer> },
+    /// `from` holds this value for the key.
+    Value { from: String, value: String },
+    AddingPeer { peer: String },
+    DialingPeer { peer: String },
+    /// An event type this crate does not know about.
+    Other(DhtResponse),
+}
+
+impl FromJson for DhtEvent {
+    fn from_json(v: &JsonValue) -> Result<DhtEvent> {
+        let mut r = DhtResponse::from_json(v)?;
+        Ok(match r.response_type {
+            0 => DhtEvent::SendingQuery { peer: r.id },
+            1 => DhtEvent::PeerResponse { peer: r.id, closer: r.responses },
+            2 if !r.responses.is_empty() => DhtEvent::FinalPeer(r.responses.remove(0)),
+            3 => DhtEvent::QueryError { peer: r.id, error: r.extra },
+            4 => DhtEvent::Provider { from: r.id, providers: r.responses },
+            5 => DhtEvent::Value { from: r.id, value: r.extra },
+            6 => DhtEvent::AddingPeer { peer: r.id },
+            7 => DhtEvent::DialingPeer { peer: r.id },
+            _ => DhtEvent::Other(r),
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileLsResponse {
+    /// Maps each requested path to the hash it resolved to.
+    pub arguments: BTreeMap<String, String>,
+    pub objects: BTreeMap<String, FileLsObject>,
+}
+
+impl FromJson for FileLsResponse {
+    fn from_json(v: &JsonValue) -> Result<FileLsResponse> {
+        Ok(FileLsResponse {
+            arguments: map(v, "Arguments")?,
+            objects: map(v, "Objects")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileLsObject {
+    pub hash: String,
+    pub size: u64,
+    pub object_type: String,
+    pub links: Vec<FileLsLink>,
+}
+
+impl FromJson for FileLsObject {
+    fn from_json(v: &JsonValue) -> Result<FileLsObject> {
+        Ok(FileLsObject {
+            hash: string(v, "Hash")?,
+            size: number_u64(v, "Size")?,
+            object_type: string(v, "Type")?,
+            links: list(v, "Links")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileLsLink {
+    pub name: String,
+    pub hash: String,
+    pub size: u64,
+    pub link_type: String,
+}
+
+impl FromJson for FileLsLink {
+    fn from_json(v: &JsonValue) -> Result<FileLsLink> {
+        Ok(FileLsLink {
+            name: string(v, "Name")?,
+            hash: string(v, "Hash")?,
+            size: number_u64(v, "Size")?,
+            link_type: string(v, "Type")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilesEntry {
+    pub name: String,
+    pub entry_type: i64,
+    pub size: u64,
+    pub hash: String,
+}
+
+impl FromJson for FilesEntry {
+    fn from_json(v: &JsonValue) -> Result<FilesEntry> {
+        Ok(FilesEntry {
+            name: string(v, "Name")?,
+            entry_type: number_i64(v, "Type")?,
+            size: number_u64(v, "Size")?,
+            hash: string(v, "Hash")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilesStatResponse {
+    pub hash: String,
+    pub size: u64,
+    pub cumulative_size: u64,
+    pub blocks: u64,
+    pub file_type: String,
+}
+
+impl FromJson for FilesStatResponse {
+    fn from_json(v: &JsonValue) -> Result<FilesStatResponse> {
+        Ok(FilesStatResponse {
+            hash: string(v, "Hash")?,
+            size: number_u64(v, "Size")?,
+            cumulative_size: number_u64(v, "CumulativeSize")?,
+            blocks: number_u64(v, "Blocks")?,
+            file_type: string(v, "Type")?,
+        })
+    }
+}
+
+/// One entry of `filestore_ls` and `filestore_verify`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FilestoreObject {
+    pub status: i64,
+    pub error_msg: Option<String>,
+    pub key: String,
+    pub file_path: String,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl FromJson for FilestoreObject {
+    fn from_json(v: &JsonValue) -> Result<FilestoreObject> {
+        Ok(FilestoreObject {
+            status: number_i64(v, "Status")?,
+            error_msg: opt_string(v, "ErrorMsg")?,
+            key: cid(v, "Key")?,
+            file_path: string(v, "FilePath")?,
+            offset: number_u64(v, "Offset")?,
+            size: number_u64(v, "Size")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdResponse {
+    pub id: String,
+    pub public_key: String,
+    pub addresses: Vec<String>,
+    pub agent_version: String,
+    pub protocol_version: String,
+}
+
+impl FromJson for IdResponse {
+    fn from_json(v: &JsonValue) -> Result<IdResponse> {
+        Ok(IdResponse {
+            id: string(v, "ID")?,
+            public_key: string(v, "PublicKey")?,
+            addresses: strings(v, "Addresses")?,
+            agent_version: string(v, "AgentVersion")?,
+            protocol_version: string(v, "ProtocolVersion")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyResponse {
+    pub name: String,
+    pub id: String,
+}
+
+impl FromJson for KeyResponse {
+    fn from_json(v: &JsonValue) -> Result<KeyResponse> {
+        Ok(KeyResponse {
+            name: string(v, "Name")?,
+            id: string(v, "Id")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyRenameResponse {
+    pub was: String,
+    pub now: String,
+    pub id: String,
+    pub overwrite: bool,
+}
+
+impl FromJson for KeyRenameResponse {
+    fn from_json(v: &JsonValue) -> Result<KeyRenameResponse> {
+        Ok(KeyRenameResponse {
+            was: string(v, "Was")?,
+            now: string(v, "Now")?,
+            id: string(v, "Id")?,
+            overwrite: boolean(v, "Overwrite")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LsObject {
+    pub hash: String,
+    pub links: Vec<LsLink>,
+}
+
+impl FromJson for LsObject {
+    fn from_json(v: &JsonValue) -> Result<LsObject> {
+        Ok(LsObject {
+            hash: string(v, "Hash")?,
+            links: list(v, "Links")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LsLink {
+    pub name: String,
+    pub hash: String,
+    pub size: u64,
+    pub link_type: i64,
+}
+
+impl FromJson for LsLink {
+    fn from_json(v: &JsonValue) -> Result<LsLink> {
+        Ok(LsLink {
+            name: string(v, "Name")?,
+            hash: string(v, "Hash")?,
+            size: number_u64(v, "Size")?,
+            link_type: number_i64(v, "Type")?,
+        })
+    }
+}
+
+/// One entry of the daemon's log, as yielded by `log_tail`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEvent {
+    /// When the entry was logged, as the daemon wrote it.
+    pub timestamp: String,
+    pub level: String,
+    /// The logging subsystem, e.g. `core` or `dht`.
+    pub subsystem: String,
+    pub message: String,
+    /// Everything else the entry carries, such as `caller` or the fields of the message.
+    pub fields: BTreeMap<String, JsonValue>,
+}
+
+impl FromJson for LogEvent {
+    fn from_json(v: &JsonValue) -> Result<LogEvent> {
+        // Current daemons log through zap; older ones used go-log's names for the same things.
+        let keys = [("ts", "time"), ("level", "level"), ("logger", "system"), ("msg", "event")];
+        let pick = |&(current, old): &(&str, &str)| -> Result<String> {
+            let key = if v[current].is_null() { old } else { current };
+            match v[key] {
+                JsonValue::Number(n) => Ok(n.to_string()),
+                _ => string(v, key),
+            }
+        };
+        let fields = v.entries()
+            .filter(|&(k, _)| !keys.iter().any(|&(current, old)| k == current || k == old))
+            .map(|(k, item)| (k.to_string(), item.clone()))
+            .collect();
+        Ok(LogEvent {
+            timestamp: pick(&keys[0])?,
+            level: pick(&keys[1])?,
+            subsystem: pick(&keys[2])?,
+            message: pick(&keys[3])?,
+            fields,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MountResponse {
+    pub ipfs: String,
+    pub ipns: String,
+    pub fuse_allow_other: bool,
+}
+
+impl FromJson for MountResponse {
+    fn from_json(v: &JsonValue) -> Result<MountResponse> {
+        Ok(MountResponse {
+            ipfs: string(v, "IPFS")?,
+            ipns: string(v, "IPNS")?,
+            fuse_allow_other: boolean(v, "FuseAllowOther")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamePublishResponse {
+    pub name: String,
+    pub value: String,
+}
+
+impl FromJson for NamePublishResponse {
+    fn from_json(v: &JsonValue) -> Result<NamePublishResponse> {
+        Ok(NamePublishResponse {
+            name: string(v, "Name")?,
+            value: string(v, "Value")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectChange {
+    pub change_type: i64,
+    pub path: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl FromJson for ObjectChange {
+    fn from_json(v: &JsonValue) -> Result<ObjectChange> {
+        Ok(ObjectChange {
+            change_type: number_i64(v, "Type")?,
+            path: string(v, "Path")?,
+            before: opt_cid(v, "Before")?,
+            after: opt_cid(v, "After")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectGetResponse {
+    pub data: String,
+    pub links: Vec<ObjectLink>,
+}
+
+impl FromJson for ObjectGetResponse {
+    fn from_json(v: &JsonValue) -> Result<ObjectGetResponse> {
+        Ok(ObjectGetResponse {
+            data: string(v, "Data")?,
+            links: list(v, "Links")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectLink {
+    pub name: String,
+    pub hash: String,
+    pub size: u64,
+}
+
+impl FromJson for ObjectLink {
+    fn from_json(v: &JsonValue) -> Result<ObjectLink> {
+        Ok(ObjectLink {
+            name: string(v, "Name")?,
+            hash: string(v, "Hash")?,
+            size: number_u64(v, "Size")?,
+        })
+    }
+}
+
+/// Returned by `object_links`, `object_new`, `object_put` and the `object_patch_*` calls.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectResponse {
+    pub hash: String,
+    pub links: Vec<ObjectLink>,
+}
+
+impl FromJson for ObjectResponse {
+    fn from_json(v: &JsonValue) -> Result<ObjectResponse> {
+        Ok(ObjectResponse {
+            hash: string(v, "Hash")?,
+            links: list(v, "Links")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjectStatResponse {
+    pub hash: String,
+    pub num_links: u64,
+    pub block_size: u64,
+    pub links_size: u64,
+    pub data_size: u64,
+    pub cumulative_size: u64,
+}
+
+impl FromJson for ObjectStatResponse {
+    fn from_json(v: &JsonValue) -> Result<ObjectStatResponse> {
+        Ok(ObjectStatResponse {
+            hash: string(v, "Hash")?,
+            num_links: number_u64(v, "NumLinks")?,
+            block_size: number_u64(v, "BlockSize")?,
+            links_size: number_u64(v, "LinksSize")?,
+            data_size: number_u64(v, "DataSize")?,
+            cumulative_size: number_u64(v, "CumulativeSize")?,
+        })
+    }
+}
+
+/// Returned by `swarm_peering_add` and `swarm_peering_rm`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PeeringResponse {
+    pub id: String,
+    pub status: String,
+}
+
+impl FromJson for PeeringResponse {
+    fn from_json(v: &JsonValue) -> Result<PeeringResponse> {
+        Ok(PeeringResponse {
+            id: string(v, "ID")?,
+            status: string(v, "Status")?,
+        })
+    }
+}
+
+/// One pinned object as listed by `pin_ls`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pin {
+    pub hash: String,
+    pub pin_type: String,
+}
+
+struct PinType(String);
+
+impl FromJson for PinType {
+    fn from_json(v: &JsonValue) -> Result<PinType> {
+        string(v, "Type").map(PinType)
+    }
+}
+
+pub(crate) fn decode_pins(bytes: &[u8]) -> Result<Vec<Pin>> {
+    let keys: BTreeMap<String, PinType> = map(&parse(bytes)?, "Keys")?;
+    Ok(keys.into_iter().map(|(hash, t)| Pin { hash, pin_type: t.0 }).collect())
+}
+
+/// A remote pinning service, as listed by `pin_remote_service_ls`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinRemoteService {
+    pub service: String,
+    pub api_endpoint: String,
+}
+
+impl FromJson for PinRemoteService {
+    fn from_json(v: &JsonValue) -> Result<PinRemoteService> {
+        Ok(PinRemoteService {
+            service: string(v, "Service")?,
+            api_endpoint: string(v, "ApiEndpoint")?,
+        })
+    }
+}
+
+/// Returned by `pin_add` and `pin_rm`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinResponse {
+    pub pins: Vec<String>,
+    pub progress: Option<u64>,
+}
+
+impl FromJson for PinResponse {
+    fn from_json(v: &JsonValue) -> Result<PinResponse> {
+        Ok(PinResponse {
+            pins: cids(v, "Pins")?,
+            progress: if v["Progress"].is_null() { None } else { Some(number_u64(v, "Progress")?) },
+        })
+    }
+}
+
+/// One pin checked by `pin_verify`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PinVerifyResponse {
+    pub cid: String,
+    pub ok: bool,
+    pub bad_nodes: Vec<BadNode>,
+}
+
+impl FromJson for PinVerifyResponse {
+    fn from_json(v: &JsonValue) -> Result<PinVerifyResponse> {
+        Ok(PinVerifyResponse {
+            cid: cid(v, "Cid")?,
+            ok: boolean(v, "Ok")?,
+            bad_nodes: list(v, "BadNodes")?,
+        })
+    }
+}
+
+/// A block below a pin that is missing or broken.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BadNode {
+    pub cid: String,
+    pub err: String,
+}
+
+impl FromJson for BadNode {
+    fn from_json(v: &JsonValue) -> Result<BadNode> {
+        Ok(BadNode {
+            cid: cid(v, "Cid")?,
+            err: string(v, "Err")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PingResponse {
+    pub success: bool,
+    /// Round trip time in nanoseconds.
+    pub time: u64,
+    pub text: String,
+}
+
+impl FromJson for PingResponse {
+    fn from_json(v: &JsonValue) -> Result<PingResponse> {
+        Ok(PingResponse {
+            success: boolean(v, "Success")?,
+            time: number_u64(v, "Time")?,
+            text: string(v, "Text")?,
+        })
+    }
+}
+
+impl PingResponse {
+    /// Whether this line reports a ping, answered or not, rather than the daemon's
+    /// running commentary (`PING ...`, `Average latency: ...`).
+    pub fn is_ping(&self) -> bool {
+        !self.success || self.text.is_empty()
+    }
+
+    /// The round trip time of an answered ping.
+    pub fn rtt(&self) -> Option<Duration> {
+        if self.success && self.text.is_empty() { Some(Duration::from_nanos(self.time)) } else { None }
+    }
+}
+
+/// Latency statistics over the pings seen so far.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PingSummary {
+    pub sent: u32,
+    pub received: u32,
+    pub min: Option<Duration>,
+    pub max: Option<Duration>,
+    total: Duration,
+}
+
+impl PingSummary {
+    pub fn new() -> PingSummary {
+        Default::default()
+    }
+
+    /// Accounts for one line of `ping`; commentary lines are skipped.
+    pub fn record(&mut self, ping: &PingResponse) {
+        if !ping.is_ping() {
+            return;
+        }
+        self.sent += 1;
+        if let Some(rtt) = ping.rtt() {
+            self.received += 1;
+            self.total += rtt;
+            self.min = Some(self.min.map_or(rtt, |min| min.min(rtt)));
+            self.max = Some(self.max.map_or(rtt, |max| max.max(rtt)));
+        }
+    }
+
+    pub fn avg(&self) -> Option<Duration> {
+        if self.received == 0 { None } else { Some(self.total / self.received) }
+    }
+
+    /// The share of pings that went unanswered, from 0 to 1.
+    pub fn loss(&self) -> f64 {
+        if self.sent == 0 { 0.0 } else { 1.0 - f64::from(self.received) / f64::from(self.sent) }
+    }
+}
+
+/// One message received by `pubsub_sub`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PubsubMessage {
+    /// The peer ID of the sender.
+    pub from: String,
+    pub data: Vec<u8>,
+    pub seqno: Vec<u8>,
+    pub topic_ids: Vec<String>,
+}
+
+/// How a daemon encodes pubsub data: Kubo 0.11 moved from plain base64 to multibase.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum PubsubEncoding {
+    /// Every message field as plain base64, the sender as its raw peer ID bytes, and topic
+    /// names as they are.
+    Base64,
+    /// The sender as peer ID text, everything else including topic names as multibase.
+    Multibase,
+}
+
+impl PubsubMessage {
+    fn decode_as(v: &JsonValue, encoding: PubsubEncoding) -> Result<PubsubMessage> {
+        let legacy = encoding == PubsubEncoding::Base64;
+        let bytes = |key: &str| -> Result<Vec<u8>> {
+            let text = string(v, key)?;
+            let decoded = if legacy { text.from_base64().ok() } else { multibase::decode(&text) };
+            decoded.ok_or_else(|| wrong_type(key, if legacy { "base64" } else { "multibase" }))
+        };
+        let topic_ids = if legacy {
+            strings(v, "topicIDs")?
+        } else {
+            strings(v, "topicIDs")?.iter()
+                .map(|t| topic(t))
+                .collect::<Option<_>>()
+                .ok_or_else(|| wrong_type("topicIDs", "multibase text"))?
+        };
+        Ok(PubsubMessage {
+            from: if legacy { multibase::base58_encode(&bytes("from")?) } else { string(v, "from")? },
+            data: bytes("data")?,
+            seqno: bytes("seqno")?,
+            topic_ids,
+        })
+    }
+}
+
+impl FromJson for PubsubMessage {
+    /// Without knowing the daemon's version, tries today's encoding first and falls back to
+    /// the old one.
+    fn from_json(v: &JsonValue) -> Result<PubsubMessage> {
+        PubsubMessage::decode_as(v, PubsubEncoding::Multibase)
+            .or_else(|_| PubsubMessage::decode_as(v, PubsubEncoding::Base64))
+    }
+}
+
+/// Decodes one `pubsub_sub` message from a daemon known to use `encoding`.
+pub(crate) fn decode_pubsub_message(bytes: &[u8], encoding: PubsubEncoding) -> Result<PubsubMessage> {
+    PubsubMessage::decode_as(&parse(bytes)?, encoding)
+}
+
+/// The decoder for `pubsub_sub` lines, given the daemon's encoding if it is known.
+pub(crate) fn pubsub_decoder(encoding: Option<PubsubEncoding>) -> fn(&[u8]) -> Result<PubsubMessage> {
+    match encoding {
+        Some(PubsubEncoding::Base64) => |bytes| decode_pubsub_message(bytes, PubsubEncoding::Base64),
+        Some(PubsubEncoding::Multibase) => |bytes| decode_pubsub_message(bytes, PubsubEncoding::Multibase),
+        None => decode::<PubsubMessage>,
+    }
+}
+
+/// One entry of `refs_local` and `filestore_dups`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RefResponse {
+    pub reference: String,
+    pub err: Option<String>,
+}
+
+impl FromJson for RefResponse {
+    fn from_json(v: &JsonValue) -> Result<RefResponse> {
+        Ok(RefResponse {
+            reference: string(v, "Ref")?,
+            err: opt_string(v, "Err")?,
+        })
+    }
+}
+
+/// A pin held by a remote pinning service, as returned by `pin_remote_add` and `pin_remote_ls`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RemotePin {
+    pub cid: String,
+    pub name: String,
+    /// `queued`, `pinning`, `pinned` or `failed`.
+    pub status: String,
+}
+
+impl FromJson for RemotePin {
+    fn from_json(v: &JsonValue) -> Result<RemotePin> {
+        Ok(RemotePin {
+            cid: cid(v, "Cid")?,
+            name: string(v, "Name")?,
+            status: string(v, "Status")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepoGcResponse {
+    pub key: String,
+    pub error: Option<String>,
+}
+
+impl FromJson for RepoGcResponse {
+    fn from_json(v: &JsonValue) -> Result<RepoGcResponse> {
+        Ok(RepoGcResponse {
+            key: cid(v, "Key")?,
+            error: opt_string(v, "Error")?,
+        })
+    }
+}
+
+/// Returned by both `repo_stat` and `stats_repo`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepoStatResponse {
+    pub num_objects: u64,
+    pub repo_size: u64,
+    pub repo_path: String,
+    pub version: String,
+    pub storage_max: u64,
+}
+
+impl FromJson for RepoStatResponse {
+    fn from_json(v: &JsonValue) -> Result<RepoStatResponse> {
+        Ok(RepoStatResponse {
+            num_objects: number_u64(v, "NumObjects")?,
+            repo_size: number_u64(v, "RepoSize")?,
+            repo_path: string(v, "RepoPath")?,
+            version: string(v, "Version")?,
+            storage_max: number_u64(v, "StorageMax")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RepoVerifyResponse {
+    pub message: String,
+    pub progress: i64,
+}
+
+impl FromJson for RepoVerifyResponse {
+    fn from_json(v: &JsonValue) -> Result<RepoVerifyResponse> {
+        Ok(RepoVerifyResponse {
+            message: string(v, "Msg")?,
+            progress: number_i64(v, "Progress")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BandwidthStats {
+    pub total_in: u64,
+    pub total_out: u64,
+    pub rate_in: f64,
+    pub rate_out: f64,
+}
+
+impl FromJson for BandwidthStats {
+    fn from_json(v: &JsonValue) -> Result<BandwidthStats> {
+        Ok(BandwidthStats {
+            total_in: number_u64(v, "TotalIn")?,
+            total_out: number_u64(v, "TotalOut")?,
+            rate_in: number_f64(v, "RateIn")?,
+            rate_out: number_f64(v, "RateOut")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwarmPeer {
+    pub addr: String,
+    pub peer: String,
+    pub latency: String,
+    pub muxer: String,
+}
+
+impl FromJson for SwarmPeer {
+    fn from_json(v: &JsonValue) -> Result<SwarmPeer> {
+        Ok(SwarmPeer {
+            addr: string(v, "Addr")?,
+            peer: string(v, "Peer")?,
+            latency: string(v, "Latency")?,
+            muxer: string(v, "Muxer")?,
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct VersionResponse {
+    pub version: String,
+    pub commit: String,
+    pub repo: String,
+    pub system: String,
+    pub golang: String,
+}
+
+impl FromJson for VersionResponse {
+    fn from_json(v: &JsonValue) -> Result<VersionResponse> {
+        Ok(VersionResponse {
+            version: string(v, "Version")?,
+            commit: string(v, "Commit")?,
+            repo: string(v, "Repo")?,
+            system: string(v, "System")?,
+            golang: string(v, "Golang")?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn add_size_is_read_from_string() {
+        let body = b"{\"Name\":\"it_works.txt\",\"Hash\":\"QmaGXbCcuNazWyCmdiHsN9bdZ1GEx1GArUvbmyzkHmotDH\",\"Size\":\"32\"}\n";
+        let added: AddResponse = decode(body).unwrap();
+        assert_eq!("it_works.txt", added.name);
+        assert_eq!(32, added.size);
+    }
+
+    #[test]
+    fn add_progress_lines_are_told_apart() {
+        let body = b"{\"Name\":\"a.txt\",\"Bytes\":262144}\n{\"Name\":\"a.txt\",\"Hash\":\"QmA\",\"Size\":\"262158\"}\n";
+        let events: Vec<AddEvent> = decode_lines(body).unwrap();
+        assert_eq!(AddEvent::Progress { name: "a.txt".to_string(), bytes: 262144 }, events[0]);
+        match events[1] {
+            AddEvent::Added(ref added) => assert_eq!("QmA", added.hash),
+            ref other => panic!("expected the added entry, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pubsub_messages_of_either_daemon_generation() {
+        let legacy = b"{\"from\":\"EiCS8Dxr9HkrWBtBC3Mr8XDn1pAHe2Fvc4aUQXLtGuZDeQ==\",\"data\":\"aGVsbG8=\",\"seqno\":\"FZ0Cf5cEOp0=\",\"topicIDs\":[\"news\"]}";
+        let msg: PubsubMessage = decode(legacy).unwrap();
+        assert!(msg.from.starts_with("Qm"));
+        assert_eq!(b"hello".to_vec(), msg.data);
+        assert_eq!(vec!["news".to_string()], msg.topic_ids);
+        let current = b"{\"from\":\"12D3KooWJ8RHKnsVTb2qwxwN8ArfC7FGbgh3GMmB8Lcq6CTmQRm7\",\"data\":\"uaGVsbG8\",\"seqno\":\"uFZ0Cf5cEOp0\",\"topicIDs\":[\"ubmV3cw\"]}";
+        let msg: PubsubMessage = decode(current).unwrap();
+        assert_eq!("12D3KooWJ8RHKnsVTb2qwxwN8ArfC7FGbgh3GMmB8Lcq6CTmQRm7", msg.from);
+        assert_eq!(b"hello".to_vec(), msg.data);
+        assert_eq!(vec!["news".to_string()], msg.topic_ids);
+        let base36 = b"{\"from\":\"k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8\",\"data\":\"uaGVsbG8\",\"seqno\":\"uFZ0Cf5cEOp0\",\"topicIDs\":[\"ubmV3cw\"]}";
+        let msg: PubsubMessage = decode(base36).unwrap();
+        assert_eq!("k51qzi5uqu5dlvj2baxnqndepeb86cbk3ng7n3i46uzyxzyqj2xjonzllnv0v8", msg.from);
+        assert_eq!(vec!["news".to_string()], msg.topic_ids);
+        let known = decode_pubsub_message(legacy, PubsubEncoding::Base64).unwrap();
+        assert_eq!(b"hello".to_vec(), known.data);
+        assert!(decode_pubsub_message(legacy, PubsubEncoding::Multibase).is_err());
+    }
+
+    #[test]
+    fn log_events_keep_extra_fields() {
+        let body = b"{\"level\":\"info\",\"ts\":\"2023-04-01T10:00:00.000Z\",\"logger\":\"core\",\"caller\":\"node/builder.go:42\",\"msg\":\"started\",\"peers\":3}";
+        let event: LogEvent = decode(body).unwrap();
+        assert_eq!(("core", "started"), (event.subsystem.as_str(), event.message.as_str()));
+        assert_eq!(vec!["caller", "peers"], event.fields.keys().collect::<Vec<_>>());
+        let old = b"{\"event\":\"handshake\",\"system\":\"secio\",\"time\":\"2017-08-01T10:00:00Z\"}";
+        let event: LogEvent = decode(old).unwrap();
+        assert_eq!(("secio", "handshake"), (event.subsystem.as_str(), event.message.as_str()));
+    }
+
+    #[test]
+    fn dht_events_are_typed() {
+        let body = b"{\"ID\":\"QmA\",\"Type\":0,\"Responses\":null,\"Extra\":\"\"}\n\
+                     {\"ID\":\"QmA\",\"Type\":4,\"Responses\":[{\"ID\":\"QmP\",\"Addrs\":[\"/ip4/1.2.3.4/tcp/4001\"]}],\"Extra\":\"\"}\n\
+                     {\"ID\":\"QmB\",\"Type\":3,\"Responses\":null,\"Extra\":\"dial backoff\"}\n";
+        let events: Vec<DhtEvent> = decode_lines(body).unwrap();
+        assert_eq!(DhtEvent::SendingQuery { peer: "QmA".to_string() }, events[0]);
+        match events[1] {
+            DhtEvent::Provider { ref providers, .. } => assert_eq!("QmP", providers[0].id),
+            ref other => panic!("expected a provider, got {:?}", other),
+        }
+        assert_eq!(DhtEvent::QueryError { peer: "QmB".to_string(), error: "dial backoff".to_string() }, events[2]);
+    }
+
+    #[test]
+    fn ping_summary_skips_commentary() {
+        let body = b"{\"Success\":true,\"Time\":0,\"Text\":\"PING QmA.\"}\n\
+                     {\"Success\":true,\"Time\":1000000,\"Text\":\"\"}\n\
+                     {\"Success\":false,\"Time\":0,\"Text\":\"Ping error: timeout\"}\n\
+                     {\"Success\":true,\"Time\":3000000,\"Text\":\"\"}\n\
+                     {\"Success\":true,\"Time\":0,\"Text\":\"Average latency: 2.00ms\"}\n";
+        let mut summary = PingSummary::new();
+        for ping in decode_lines::<PingResponse>(body).unwrap() {
+            summary.record(&ping);
+        }
+        assert_eq!((3, 2), (summary.sent, summary.received));
+        assert_eq!(Some(Duration::from_millis(1)), summary.min);
+        assert_eq!(Some(Duration::from_millis(2)), summary.avg());
+        assert_eq!(Some(Duration::from_millis(3)), summary.max);
+        assert!((summary.loss() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn cids_accept_links_and_strings() {
+        let body = b"{\"Keys\":[{\"/\":\"QmA\"},\"QmB\"]}";
+        assert_eq!(vec!["QmA".to_string(), "QmB".to_string()], decode_cids(body, "Keys").unwrap());
+    }
+
+    #[test]
+    fn ndjson_is_decoded_line_by_line() {
+        let body = b"{\"Success\":true,\"Time\":0,\"Text\":\"PING QmA.\"}\n{\"Success\":true,\"Time\":1200,\"Text\":\"\"}\n";
+        let pings: Vec<PingResponse> = decode_lines(body).unwrap();
+        assert_eq!(2, pings.len());
+        assert_eq!(1200, pings[1].time);
+    }
+
+    #[test]
+    fn wrong_types_are_json_errors() {
+        match decode::<VersionResponse>(b"{\"Version\":4}") {
+            Err(IpfsError::Json(_)) => {}
+            other => panic!("expected a json error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn pins_are_listed_by_hash() {
+        let body = b"{\"Keys\":{\"QmB\":{\"Type\":\"recursive\"},\"QmA\":{\"Type\":\"direct\"}}}";
+        let pins = decode_pins(body).unwrap();
+        assert_eq!(Pin { hash: "QmA".to_string(), pin_type: "direct".to_string() }, pins[0]);
+    }
+
+    #[test]
+    fn topics_that_are_not_multibase_are_kept() {
+        let body = b"{\"Strings\":[\"ubmV3cw\",\"weather\"]}";
+        assert_eq!(vec!["news", "weather"], decode_topics(body, None).unwrap());
+        assert_eq!(vec!["news", "weather"], decode_topics(body, Some(PubsubEncoding::Multibase)).unwrap());
+        assert_eq!(vec!["ubmV3cw", "weather"], decode_topics(body, Some(PubsubEncoding::Base64)).unwrap());
+    }
+
+    #[test]
+    fn dag_import_keeps_only_roots() {
+        let body = b"{\"Root\":{\"Cid\":{\"/\":\"bafyA\"},\"PinErrorMsg\":\"\"}}\n\
+                     {\"Root\":{\"Cid\":{\"/\":\"bafyB\"},\"PinErrorMsg\":\"not pinned\"}}\n\
+                     {\"Stats\":{\"BlockCount\":4,\"BlockBytesCount\":512}}\n";
+        let roots = decode_dag_import(body).unwrap();
+        assert_eq!(2, roots.len());
+        assert_eq!(DagImportRoot { cid: "bafyA".to_string(), pin_error: None }, roots[0]);
+        assert_eq!(Some("not pinned".to_string()), roots[1].pin_error);
+    }
+
+    #[test]
+    fn listings_can_be_bare_arrays() {
+        let body = b"[{\"Code\":98,\"Name\":\"base32\"},{\"Code\":122,\"Name\":\"base58btc\"}]";
+        let bases: Vec<CodeAndName> = decode_array(body).unwrap();
+        assert_eq!(CodeAndName { code: 122, name: "base58btc".to_string() }, bases[1]);
+        assert!(decode_array::<CodeAndName>(b"{\"Code\":98}").is_err());
+    }
+
+}
@@ -0,0 +1,285 @@
+use std::cell::Cell;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::str;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use curl::easy::{Easy, ReadError};
+
+use crate::error::{IpfsError, Result};
+use crate::multipart::MultipartReader;
+
+/// How many chunks a `ResponseReader` buffers ahead of its reader.
+const READ_AHEAD: usize = 16;
+
+/// How many idle handles a `Pool` keeps around.
+const MAX_IDLE: usize = 8;
+
+/// Picks the status code out of an HTTP status line, ignoring every other header.
+fn status_code(header: &[u8]) -> Option<u32> {
+    if !header.starts_with(b"HTTP/") {
+        return None;
+    }
+    str::from_utf8(header).ok()?.split_whitespace().nth(1)?.parse().ok()
+}
+
+fn is_success(code: u32) -> bool {
+    (200..300).contains(&code)
+}
+
+/// Feeds the request body to curl, keeping the error around since curl can only abort.
+fn read_body(body: &mut MultipartReader, buf: &mut [u8], error: &mut Option<io::Error>) -> std::result::Result<usize, ReadError> {
+    body.read(buf).map_err(|e| {
+        *error = Some(e);
+        ReadError::Abort
+    })
+}
+
+/// Idle curl handles shared by the clones of a client.
+///
+/// Each handle keeps its connections open, so taking one from the pool reuses a keep-alive
+/// connection to the daemon instead of opening a new one for every call.
+#[derive(Clone, Default)]
+pub(crate) struct Pool {
+    idle: Arc<Mutex<Vec<Easy>>>,
+}
+
+impl Pool {
+    /// An idle handle with every option back at its default, or a new one.
+    pub fn take(&self) -> Easy {
+        let idle = self.idle.lock().unwrap_or_else(|e| e.into_inner()).pop();
+        match idle {
+            Some(mut handle) => {
+                handle.reset();
+                handle
+            }
+            None => Easy::new(),
+        }
+    }
+
+    /// Hands back a handle whose transfer went through, so its connection can be reused.
+    pub fn put(&self, handle: Easy) {
+        let mut idle = self.idle.lock().unwrap_or_else(|e| e.into_inner());
+        if idle.len() < MAX_IDLE {
+            idle.push(handle);
+        }
+    }
+}
+
+/// A configured curl handle together with the request body it uploads, if any.
+pub(crate) struct Call {
+    pub handle: Easy,
+    pub body: Option<MultipartReader>,
+    /// Where the handle goes back once the transfer is over.
+    pub pool: Pool,
+}
+
+/// Runs the transfer, copying the body into `out` as it arrives.
+///
+/// Error bodies are never written to `out`; they are decoded into the returned error instead.
+pub(crate) fn perform_into<W: Write + ?Sized>(call: Call, out: &mut W) -> Result<u64> {
+    let Call { mut handle, mut body, pool } = call;
+    let status = Cell::new(0);
+    let mut error_body = Vec::new();
+    let mut written = 0;
+    let mut write_error = None;
+    let mut read_error = None;
+    let performed = {
+        let mut transfer = handle.transfer();
+        if let Some(body) = body.as_mut() {
+            let read_error = &mut read_error;
+            transfer.read_function(move |buf| read_body(body, buf, read_error))?;
+        }
+        transfer.header_function(|header| {
+            if let Some(code) = status_code(header) {
+                status.set(code);
+            }
+            true
+        })?;
+        transfer.write_function(|new_data| {
+            if !is_success(status.get()) {
+                error_body.extend_from_slice(new_data);
+                return Ok(new_data.len());
+            }
+            match out.write_all(new_data) {
+                Ok(()) => {
+                    written += new_data.len() as u64;
+                    Ok(new_data.len())
+                }
+                Err(e) => {
+                    write_error = Some(e);
+                    // Anything short of the full length aborts the transfer.
+                    Ok(0)
+                }
+            }
+        })?;
+        transfer.perform()
+    };
+    if let Some(e) = write_error.or(read_error) {
+        return Err(IpfsError::Io(e));
+    }
+    performed?;
+    let code = handle.response_code()?;
+    pool.put(handle);
+    match code {
+        200..=299 => Ok(written),
+        code => Err(IpfsError::from_response(code, &error_body)),
+    }
+}
+
+enum Event {
+    /// Headers arrived with a 2xx status; the body follows.
+    Ready,
+    Data(Vec<u8>),
+    Failed(IpfsError),
+}
+
+/// A daemon response read incrementally while the transfer runs on a background thread.
+///
+/// At most a few chunks are buffered ahead of the reader, so arbitrarily large bodies can be
+/// piped through with bounded memory. Dropping the reader aborts the transfer.
+pub struct ResponseReader {
+    events: Receiver<Event>,
+    chunk: Vec<u8>,
+    pos: usize,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ResponseReader {
+    /// Starts the transfer and waits until the daemon has answered with a status.
+    pub(crate) fn spawn(call: Call) -> Result<ResponseReader> {
+        let (tx, rx) = sync_channel(READ_AHEAD);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let flag = cancelled.clone();
+        thread::spawn(move || {
+            if let Err(e) = stream(call, &tx, &flag) {
+                let _ = tx.send(Event::Failed(e));
+            }
+        });
+        let reader = ResponseReader { events: rx, chunk: Vec::new(), pos: 0, cancelled };
+        match reader.events.recv() {
+            Ok(Event::Ready) => Ok(reader),
+            Ok(Event::Failed(e)) => Err(e),
+            // The transfer thread only sends data after `Ready`.
+            Ok(Event::Data(_)) => unreachable!(),
+            Err(_) => Err(IpfsError::Io(io::Error::new(io::ErrorKind::BrokenPipe, "transfer ended early"))),
+        }
+    }
+}
+
+fn stream(call: Call, tx: &SyncSender<Event>, cancelled: &AtomicBool) -> Result<()> {
+    let Call { mut handle, mut body, pool } = call;
+    let status = Cell::new(0);
+    let ready = Cell::new(false);
+    let mut error_body = Vec::new();
+    let mut read_error = None;
+    handle.progress(true)?;
+    let performed = {
+        let mut transfer = handle.transfer();
+        if let Some(body) = body.as_mut() {
+            let read_error = &mut read_error;
+            transfer.read_function(move |buf| read_body(body, buf, read_error))?;
+        }
+        transfer.header_function(|header| {
+            if let Some(code) = status_code(header) {
+                status.set(code);
+            } else if header == b"\r\n" && is_success(status.get()) && !ready.get() {
+                ready.set(true);
+                return tx.send(Event::Ready).is_ok();
+            }
+            true
+        })?;
+        transfer.write_function(|new_data| {
+            if !ready.get() {
+                error_body.extend_from_slice(new_data);
+                return Ok(new_data.len());
+            }
+            match tx.send(Event::Data(new_data.to_vec())) {
+                Ok(()) => Ok(new_data.len()),
+                // The reader is gone; abort.
+                Err(_) => Ok(0),
+            }
+        })?;
+        // Lets an idle transfer notice that the reader was dropped.
+        transfer.progress_function(|_, _, _, _| !cancelled.load(Ordering::SeqCst))?;
+        transfer.perform()
+    };
+    if cancelled.load(Ordering::SeqCst) {
+        return Ok(());
+    }
+    if let Some(e) = read_error {
+        return Err(IpfsError::Io(e));
+    }
+    performed?;
+    let code = handle.response_code()?;
+    pool.put(handle);
+    match code {
+        200..=299 => {
+            // A bodiless reply never hits the blank-line check above.
+            if !ready.get() {
+                let _ = tx.send(Event::Ready);
+            }
+            Ok(())
+        }
+        code => Err(IpfsError::from_response(code, &error_body)),
+    }
+}
+
+impl Read for ResponseReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.chunk.len() {
+            match self.events.recv() {
+                Ok(Event::Data(data)) => {
+                    self.chunk = data;
+                    self.pos = 0;
+                }
+                Ok(Event::Ready) => {}
+                Ok(Event::Failed(e)) => return Err(io::Error::other(e)),
+                // The transfer thread hung up: the body is complete.
+                Err(_) => return Ok(0),
+            }
+        }
+        let n = (&self.chunk[self.pos..]).read(buf)?;
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl Drop for ResponseReader {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A newline-delimited JSON response, decoded one object at a time as the daemon sends it.
+///
+/// Like `ResponseReader`, dropping it aborts the transfer.
+pub struct JsonLines<T> {
+    reader: BufReader<ResponseReader>,
+    decode: fn(&[u8]) -> Result<T>,
+    line: Vec<u8>,
+}
+
+impl<T> JsonLines<T> {
+    pub(crate) fn new(reader: ResponseReader, decode: fn(&[u8]) -> Result<T>) -> JsonLines<T> {
+        JsonLines { reader: BufReader::new(reader), decode, line: Vec::new() }
+    }
+}
+
+impl<T> Iterator for JsonLines<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        loop {
+            self.line.clear();
+            match self.reader.read_until(b'\n', &mut self.line) {
+                Ok(0) => return None,
+                Ok(_) if self.line.iter().all(u8::is_ascii_whitespace) => continue,
+                Ok(_) => return Some((self.decode)(&self.line)),
+                Err(e) => return Some(Err(IpfsError::from_io(e))),
+            }
+        }
+    }
+}